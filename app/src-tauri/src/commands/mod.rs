@@ -1,3 +1,6 @@
 pub mod video;
+pub mod scan;
+pub mod summary;
+pub mod watch;
 mod thumbnail;
 pub mod license;
\ No newline at end of file
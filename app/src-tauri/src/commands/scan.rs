@@ -0,0 +1,1019 @@
+// Free FPS - Video Frame Rate Converter
+// Copyright (C) 2025 undelalune
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+// Input folder scanning shared by `get_video_files`, `scan_video_files` and
+// `convert_videos`.
+
+use crate::commands::video::{ConversionController, VideoFile};
+use crate::errors::{AppError, AppErrorCode, AppResult};
+use crate::utils::bundled_ffmpeg::{get_ffmpeg_tools, FfmpegTools};
+use crate::utils::camera_card::{card_clip_dirs, read_camera_clip};
+use crate::utils::chapters::{find_chapter_sequences, ChapterCandidate, ChapterGroup};
+use crate::utils::ffmpeg::{ProbeError, VideoProbe};
+use crate::utils::hashing::{full_hash, quick_hash};
+use crate::utils::partial_output::is_partial_path;
+use crate::utils::probe_cache::probe_video_cached;
+use crate::utils::scan_filter::ScanFilter;
+use crate::utils::sniff::{read_header, sniff_container};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::{Component, Path, PathBuf};
+use tauri::{AppHandle, Emitter, State};
+use tokio::fs as async_fs;
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+
+const DEFAULT_EXTENSIONS: [&str; 14] = [
+    "mp4", "mkv", "avi", "mov", "webm", "m4v", "mts", "m2ts", "mpg", "wmv", "flv", "3gp", "ts",
+    "mxf",
+];
+
+/// Order of scanned files, which is also the order batches are converted in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum SortKey {
+    /// Natural order of the subfolder, then the file name
+    #[default]
+    Name,
+    Size,
+    /// File modification time
+    Modified,
+    /// Recording time from the camera sidecar or the container's creation_time
+    CreationTime,
+    Duration,
+    /// Source frame rate
+    Fps,
+}
+
+impl SortKey {
+    pub fn needs_probe(self) -> bool {
+        matches!(self, Self::CreationTime | Self::Duration | Self::Fps)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScanOptions {
+    /// Descend into subfolders
+    pub recursive: bool,
+    /// Deepest subfolder level to scan (1 = direct subfolders), unlimited when not set
+    pub max_depth: Option<u32>,
+    /// Follow symlinked files and folders. Targets must still resolve inside
+    /// the input folder.
+    pub follow_symlinks: bool,
+    /// Extensions treated as videos, without the dot
+    pub extensions: Vec<String>,
+    /// Also pick up files with other extensions whose header looks like a video,
+    /// and probe every candidate to drop files without a video stream
+    pub sniff: bool,
+    pub filter: ScanFilter,
+    /// Report chaptered recordings in `ScanResult::chapters`. Files are probed
+    /// when ffmpeg is available, so stream layout and timing can be compared.
+    pub detect_chapters: bool,
+    /// Hash files of equal size and set `VideoFile::duplicate_of` on copies
+    pub detect_duplicates: bool,
+    pub sort_by: SortKey,
+    pub descending: bool,
+}
+
+impl Default for ScanOptions {
+    fn default() -> Self {
+        Self {
+            recursive: false,
+            max_depth: None,
+            follow_symlinks: false,
+            extensions: DEFAULT_EXTENSIONS.iter().map(|e| e.to_string()).collect(),
+            sniff: false,
+            filter: ScanFilter::default(),
+            detect_chapters: false,
+            detect_duplicates: false,
+            sort_by: SortKey::Name,
+            descending: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum SkipReason {
+    /// Extension not in the scan's list and not sniffed as a video
+    UnsupportedExtension,
+    /// Probed, but has no video stream (e.g. audio-only .mp4/.m4a)
+    NoVideoStream,
+    ProbeFailed,
+    /// Rejected by the scan filter
+    Filtered,
+    /// Symlink while `follow_symlinks` is off
+    Symlink,
+    /// Symlink target outside the scanned folder
+    OutsideFolder,
+    Unreadable,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkippedFile {
+    pub path: String,
+    pub name: String,
+    pub reason: SkipReason,
+    pub details: Option<String>,
+}
+
+impl SkippedFile {
+    fn new(path: &Path, reason: SkipReason, details: Option<String>) -> Self {
+        Self {
+            path: path.to_string_lossy().to_string(),
+            name: file_name(path),
+            reason,
+            details,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ScanResult {
+    pub files: Vec<VideoFile>,
+    pub skipped: Vec<SkippedFile>,
+    /// Chaptered recordings, when `detect_chapters` is set
+    #[serde(default)]
+    pub chapters: Vec<ChapterGroup>,
+}
+
+/// Payload of the `scan-progress` event, sent for every accepted file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScanFileEvent {
+    /// Echoes the id passed by the caller, so events of an outdated scan can be ignored
+    pub scan_id: u32,
+    /// 0-based position in discovery order
+    pub index: usize,
+    pub file: VideoFile,
+    pub probe: Option<VideoProbe>,
+}
+
+/// Payload of the `scan-finished` event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScanSummary {
+    pub scan_id: u32,
+    pub found: usize,
+    pub skipped: Vec<SkippedFile>,
+    pub chapters: Vec<ChapterGroup>,
+    /// Path of each duplicate -> path of the identical file listed earlier.
+    /// Duplicates are only known once the walk is done, after their
+    /// `scan-progress` events were sent.
+    pub duplicates: HashMap<String, String>,
+    pub cancelled: bool,
+    pub error: Option<AppError>,
+}
+
+/// Name of the output folder created inside the input folder when no
+/// output folder is set.
+pub fn default_output_dir_name(target_fps: f32) -> String {
+    format!("converted_videos_{}fps", target_fps)
+}
+
+/// True for folders named like [`default_output_dir_name`], whatever the fps.
+fn is_default_output_dir(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|n| n.to_str())
+        .and_then(|n| n.strip_prefix("converted_videos_"))
+        .and_then(|n| n.strip_suffix("fps"))
+        .is_some_and(|fps| fps.parse::<f32>().is_ok())
+}
+
+async fn sniff_file(path: &Path) -> Option<String> {
+    let header = read_header(path).await.ok()?;
+    sniff_container(&header).map(str::to_string)
+}
+
+/// Container of a file picked without a scan, when its extension is not a
+/// default video extension.
+pub(crate) async fn sniff_misnamed(path: &Path) -> Option<String> {
+    let known = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| DEFAULT_EXTENSIONS.contains(&e.to_lowercase().as_str()));
+    if known {
+        return None;
+    }
+    sniff_file(path).await
+}
+
+/// Outputs are named "<stem>_<fps>fps.<ext>"; only such files are probed
+/// for a provenance tag during scans, so plain sources cost nothing extra.
+fn looks_like_output_name(path: &Path) -> bool {
+    path.file_stem()
+        .and_then(|s| s.to_str())
+        .and_then(|s| s.strip_suffix("fps"))
+        .and_then(|s| s.rsplit_once('_'))
+        .is_some_and(|(_, fps)| !fps.is_empty() && fps.parse::<f32>().is_ok())
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string()
+}
+
+/// Folder of `path` relative to `base`, "" for files at the top level.
+/// Falls back to the canonical paths when `path` is not spelled below `base`;
+/// never contains `..` so it is safe to join onto an output folder.
+pub fn relative_dir(path: &Path, base: &Path) -> String {
+    let strip = |path: &Path, base: &Path| {
+        path.parent()
+            .and_then(|p| p.strip_prefix(base).ok())
+            .filter(|rel| rel.components().all(|c| matches!(c, Component::Normal(_))))
+            .map(|rel| rel.to_string_lossy().to_string())
+    };
+    strip(path, base)
+        .or_else(|| {
+            let path = path.canonicalize().ok()?;
+            let base = base.canonicalize().ok()?;
+            strip(&path, &base)
+        })
+        .unwrap_or_default()
+}
+
+/// Exclude list for a scan that previews a conversion into `output_folder`.
+/// The default output folder is always skipped by name, so only a custom
+/// one needs listing.
+pub(crate) fn output_exclude(output_folder: Option<&str>) -> Vec<PathBuf> {
+    output_folder
+        .filter(|f| !f.trim().is_empty())
+        .and_then(|f| Path::new(f).canonicalize().ok())
+        .into_iter()
+        .collect()
+}
+
+/// List the videos in `folder_path`. `exclude` holds folders that are never
+/// entered, e.g. an output folder that lives inside the input folder.
+pub async fn list_video_files(
+    folder_path: String,
+    options: &ScanOptions,
+    exclude: &[PathBuf],
+    tools: Option<&FfmpegTools>,
+    cancel: CancellationToken,
+) -> AppResult<ScanResult> {
+    scan_folder(folder_path, options, exclude, tools, None, cancel).await
+}
+
+/// [`list_video_files`] that also sends every accepted file to `found` as soon
+/// as it is discovered (in discovery order, the result is sorted).
+async fn scan_folder(
+    folder_path: String,
+    options: &ScanOptions,
+    exclude: &[PathBuf],
+    tools: Option<&FfmpegTools>,
+    found: Option<&mpsc::UnboundedSender<VideoFile>>,
+    cancel: CancellationToken,
+) -> AppResult<ScanResult> {
+    let path = Path::new(&folder_path);
+    if !async_fs::try_exists(path).await.unwrap_or(false) {
+        return Err(AppError::code_only(AppErrorCode::FolderNotFound));
+    }
+
+    // Canonicalize the base folder for security validation
+    let base_canonical = async_fs::canonicalize(path)
+        .await
+        .map_err(|e| AppError::new(AppErrorCode::InvalidInputPath, e.to_string()))?;
+
+    let filter = options
+        .filter
+        .compile()
+        .map_err(|e| AppError::new(AppErrorCode::InvalidScanFilter, e))?;
+    // Sniffed candidates are probed to drop audio-only and broken files
+    if options.sniff && tools.is_none() {
+        return Err(AppError::new(
+            AppErrorCode::FfmpegNotFound,
+            "Sniffing file contents needs ffmpeg".to_string(),
+        ));
+    }
+    if filter.needs_probe() && tools.is_none() {
+        return Err(AppError::new(
+            AppErrorCode::FfmpegNotFound,
+            "Probe-based filters need ffmpeg".to_string(),
+        ));
+    }
+    if options.sort_by.needs_probe() && tools.is_none() {
+        return Err(AppError::new(
+            AppErrorCode::FfmpegNotFound,
+            format!("Sorting by {:?} needs ffmpeg", options.sort_by),
+        ));
+    }
+
+    let extensions: Vec<String> = options
+        .extensions
+        .iter()
+        .map(|e| e.trim().trim_start_matches('.').to_lowercase())
+        .collect();
+    let mut video_files = Vec::new();
+    let mut skipped = Vec::new();
+    // Canonical paths already visited, so symlinks can't produce loops or duplicates
+    let mut seen_dirs: HashSet<PathBuf> = HashSet::from([base_canonical.clone()]);
+    let mut seen_files: HashSet<PathBuf> = HashSet::new();
+    let mut pending: Vec<(PathBuf, u32)> = vec![(path.to_path_buf(), 0)];
+
+    // A camera card root holds no clips itself; its clip folders are scanned
+    // even when the scan is not recursive
+    for (_, clip_dir) in card_clip_dirs(path).await {
+        if let Ok(canonical) = async_fs::canonicalize(&clip_dir).await {
+            if canonical.starts_with(&base_canonical) && seen_dirs.insert(canonical) {
+                pending.push((clip_dir, 1));
+            }
+        }
+    }
+
+    while let Some((dir_path, depth)) = pending.pop() {
+        // The top level must be readable; unreadable subfolders are skipped
+        let mut dir = match async_fs::read_dir(&dir_path).await {
+            Ok(d) => d,
+            Err(e) if depth == 0 => return Err(AppError::from(e)),
+            Err(e) => {
+                skipped.push(SkippedFile::new(
+                    &dir_path,
+                    SkipReason::Unreadable,
+                    Some(e.to_string()),
+                ));
+                continue;
+            }
+        };
+
+        while let Some(entry) = dir.next_entry().await.map_err(AppError::from)? {
+            if cancel.is_cancelled() {
+                return Err(AppError::code_only(AppErrorCode::Cancelled));
+            }
+
+            let file_type = entry.file_type().await.map_err(AppError::from)?;
+            let file_path = entry.path();
+
+            if file_type.is_symlink() && !options.follow_symlinks {
+                // Only symlinked files are worth reporting
+                if async_fs::metadata(&file_path)
+                    .await
+                    .is_ok_and(|m| m.is_file())
+                {
+                    skipped.push(SkippedFile::new(&file_path, SkipReason::Symlink, None));
+                }
+                continue;
+            }
+
+            // Security: Ensure the entry (or its symlink target) is within the base folder
+            let canonical = match async_fs::canonicalize(&file_path).await {
+                Ok(c) if c.starts_with(&base_canonical) => c,
+                Ok(c) => {
+                    if c.is_file() {
+                        skipped.push(SkippedFile::new(
+                            &file_path,
+                            SkipReason::OutsideFolder,
+                            None,
+                        ));
+                    }
+                    continue;
+                }
+                Err(e) => {
+                    skipped.push(SkippedFile::new(
+                        &file_path,
+                        SkipReason::Unreadable,
+                        Some(e.to_string()),
+                    ));
+                    continue;
+                }
+            };
+
+            if canonical.is_dir() {
+                let within_depth = options.max_depth.is_none_or(|max| depth < max);
+                if options.recursive
+                    && within_depth
+                    && !is_default_output_dir(&file_path)
+                    && !exclude.iter().any(|e| canonical.starts_with(e))
+                    && seen_dirs.insert(canonical.clone())
+                {
+                    pending.push((file_path, depth + 1));
+                }
+                continue;
+            }
+            if !canonical.is_file() || !seen_files.insert(canonical.clone()) {
+                continue;
+            }
+            // Output being written by a running conversion
+            if is_partial_path(&file_path) {
+                continue;
+            }
+
+            let known_extension = file_path
+                .extension()
+                .and_then(|e| e.to_str())
+                .is_some_and(|e| extensions.contains(&e.to_lowercase()));
+
+            // Extensionless or misnamed files are taken in when their header looks like a video
+            let mut container = None;
+            if !known_extension {
+                if options.sniff {
+                    container = sniff_file(&canonical).await;
+                }
+                if container.is_none() {
+                    skipped.push(SkippedFile::new(
+                        &file_path,
+                        SkipReason::UnsupportedExtension,
+                        None,
+                    ));
+                    continue;
+                }
+            }
+
+            let name = file_name(&file_path);
+            let rel_dir = relative_dir(&file_path, path);
+            let size = async_fs::metadata(&canonical)
+                .await
+                .map_err(AppError::from)?
+                .len();
+
+            // Cheap checks first, so excluded files are never probed
+            let rel_path = Path::new(&rel_dir).join(&name);
+            let rel_path = rel_path.to_string_lossy().replace('\\', "/");
+            if let Some(why) = filter.reject_file(&name, &rel_path, size) {
+                skipped.push(SkippedFile::new(
+                    &file_path,
+                    SkipReason::Filtered,
+                    Some(why),
+                ));
+                continue;
+            }
+
+            // Sniffing and probe-based filters probe every candidate, so audio-only and
+            // broken files are dropped here instead of failing at conversion; otherwise
+            // only likely outputs are probed
+            let must_probe = options.sniff || filter.needs_probe();
+            let file_str = file_path.to_string_lossy().to_string();
+            let probe = match tools {
+                Some(t) if must_probe || looks_like_output_name(&file_path) => {
+                    Some(probe_video_cached(t.ffprobe.as_deref(), &t.ffmpeg, &file_str).await)
+                }
+                _ => None,
+            };
+            let provenance = match probe {
+                Some(Ok(p)) => {
+                    if let Some(why) = filter.reject_probe(&p) {
+                        skipped.push(SkippedFile::new(
+                            &file_path,
+                            SkipReason::Filtered,
+                            Some(why),
+                        ));
+                        continue;
+                    }
+                    p.provenance
+                }
+                Some(Err(e)) if must_probe => {
+                    let reason = match e {
+                        ProbeError::NoVideoStream => SkipReason::NoVideoStream,
+                        ProbeError::Failed(_) => SkipReason::ProbeFailed,
+                    };
+                    skipped.push(SkippedFile::new(&file_path, reason, Some(e.to_string())));
+                    continue;
+                }
+                _ => None,
+            };
+
+            let video_file = VideoFile {
+                path: file_str,
+                name,
+                relative_dir: rel_dir,
+                size,
+                thumbnail: None,
+                provenance,
+                clip: read_camera_clip(&file_path).await,
+                duplicate_of: None,
+                container,
+            };
+            if let Some(tx) = found {
+                let _ = tx.send(video_file.clone());
+            }
+            video_files.push(video_file);
+        }
+    }
+
+    sort_video_files(&mut video_files, options, tools).await;
+
+    skipped.sort_by(|a, b| natural_cmp_ignore_case(&a.path, &b.path));
+
+    if options.detect_duplicates {
+        mark_duplicates(&mut video_files, &cancel).await?;
+    }
+
+    let chapters = if options.detect_chapters {
+        detect_chapters(&video_files, tools).await
+    } else {
+        Vec::new()
+    };
+
+    Ok(ScanResult {
+        files: video_files,
+        skipped,
+        chapters,
+    })
+}
+
+/// Sort by `options.sort_by`. Name order breaks ties, and files without a
+/// value for the key (no creation time, unreadable) go last in both directions.
+pub async fn sort_video_files(
+    files: &mut Vec<VideoFile>,
+    options: &ScanOptions,
+    tools: Option<&FfmpegTools>,
+) {
+    let by_name = |a: &VideoFile, b: &VideoFile| {
+        natural_cmp_ignore_case(&a.relative_dir, &b.relative_dir)
+            .then_with(|| natural_cmp_ignore_case(&a.name, &b.name))
+    };
+    if options.sort_by == SortKey::Name {
+        files.sort_by(|a, b| {
+            let order = by_name(a, b);
+            if options.descending {
+                order.reverse()
+            } else {
+                order
+            }
+        });
+        return;
+    }
+
+    let mut keyed = Vec::with_capacity(files.len());
+    for f in files.drain(..) {
+        let key = sort_value(&f, options.sort_by, tools).await;
+        keyed.push((key, f));
+    }
+    keyed.sort_by(|(ka, a), (kb, b)| {
+        let order = match (ka, kb) {
+            (Some(x), Some(y)) => {
+                let o = x.total_cmp(y);
+                if options.descending {
+                    o.reverse()
+                } else {
+                    o
+                }
+            }
+            (Some(_), None) => std::cmp::Ordering::Less,
+            (None, Some(_)) => std::cmp::Ordering::Greater,
+            (None, None) => std::cmp::Ordering::Equal,
+        };
+        order.then_with(|| by_name(a, b))
+    });
+    files.extend(keyed.into_iter().map(|(_, f)| f));
+}
+
+async fn sort_value(file: &VideoFile, key: SortKey, tools: Option<&FfmpegTools>) -> Option<f64> {
+    let timestamp = |t: &str| {
+        chrono::DateTime::parse_from_rfc3339(t)
+            .ok()
+            .map(|dt| dt.timestamp_millis() as f64)
+    };
+    match key {
+        SortKey::Name => None,
+        SortKey::Size => Some(file.size as f64),
+        SortKey::Modified => async_fs::metadata(&file.path)
+            .await
+            .ok()
+            .and_then(|m| m.modified().ok())
+            .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+            .map(|d| d.as_secs_f64() * 1000.0),
+        SortKey::CreationTime | SortKey::Duration | SortKey::Fps => {
+            if key == SortKey::CreationTime {
+                if let Some(t) = file.clip.as_ref().and_then(|c| c.creation_time.as_deref()) {
+                    return timestamp(t);
+                }
+            }
+            let t = tools?;
+            let probe = probe_video_cached(t.ffprobe.as_deref(), &t.ffmpeg, &file.path)
+                .await
+                .ok()?;
+            match key {
+                SortKey::CreationTime => probe.creation_time.as_deref().and_then(timestamp),
+                // Unknown durations are reported as 0
+                SortKey::Duration => Some(probe.duration_sec).filter(|d| *d > 0.0),
+                _ => Some(probe.fps),
+            }
+        }
+    }
+}
+
+/// Set `duplicate_of` on every file identical to an earlier one. Only files
+/// of equal size are hashed: the quick hash narrows them down and a full hash
+/// confirms. Unreadable files are never reported as duplicates.
+pub async fn mark_duplicates(files: &mut [VideoFile], cancel: &CancellationToken) -> AppResult<()> {
+    let mut by_size: HashMap<u64, Vec<usize>> = HashMap::new();
+    for (i, f) in files.iter().enumerate() {
+        if f.size > 0 {
+            by_size.entry(f.size).or_default().push(i);
+        }
+    }
+
+    for same_size in by_size.into_values().filter(|g| g.len() > 1) {
+        let mut by_quick: HashMap<String, Vec<usize>> = HashMap::new();
+        for i in same_size {
+            if cancel.is_cancelled() {
+                return Err(AppError::code_only(AppErrorCode::Cancelled));
+            }
+            if let Ok(h) = quick_hash(Path::new(&files[i].path)).await {
+                by_quick.entry(h).or_default().push(i);
+            }
+        }
+
+        for candidates in by_quick.into_values().filter(|g| g.len() > 1) {
+            // First file (in list order) with each full hash
+            let mut originals: HashMap<String, usize> = HashMap::new();
+            for i in candidates {
+                if cancel.is_cancelled() {
+                    return Err(AppError::code_only(AppErrorCode::Cancelled));
+                }
+                let Ok(h) = full_hash(Path::new(&files[i].path)).await else {
+                    continue;
+                };
+                match originals.get(&h) {
+                    Some(&first) => files[i].duplicate_of = Some(files[first].path.clone()),
+                    None => {
+                        originals.insert(h, i);
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn detect_chapters(files: &[VideoFile], tools: Option<&FfmpegTools>) -> Vec<ChapterGroup> {
+    let mut modified = Vec::with_capacity(files.len());
+    let mut probes = Vec::with_capacity(files.len());
+    for f in files {
+        modified.push(
+            async_fs::metadata(&f.path)
+                .await
+                .ok()
+                .and_then(|m| m.modified().ok()),
+        );
+        probes.push(match tools {
+            Some(t) => probe_video_cached(t.ffprobe.as_deref(), &t.ffmpeg, &f.path)
+                .await
+                .ok(),
+            None => None,
+        });
+    }
+    let candidates: Vec<ChapterCandidate> = files
+        .iter()
+        .enumerate()
+        .map(|(i, f)| ChapterCandidate {
+            path: &f.path,
+            modified: modified[i],
+            probe: probes[i].as_ref(),
+        })
+        .collect();
+
+    find_chapter_sequences(&candidates)
+        .into_iter()
+        .map(|seq| ChapterGroup {
+            name: files[seq[0]].name.clone(),
+            duration: seq
+                .iter()
+                .map(|&i| probes[i].as_ref().map(|p| p.duration_sec))
+                .sum(),
+            files: seq.iter().map(|&i| files[i].path.clone()).collect(),
+        })
+        .collect()
+}
+
+/// Streaming variant of `get_video_files`: emits `scan-progress` for each file
+/// as it is found (with probe data when `with_probe` is set) and
+/// `scan-finished` with the summary at the end. Starting a scan cancels the
+/// previous streaming scan.
+#[tauri::command]
+pub async fn scan_video_files(
+    app: AppHandle,
+    folder_path: String,
+    scan_id: u32,
+    options: Option<ScanOptions>,
+    with_probe: Option<bool>,
+    output_folder: Option<String>,
+    state: State<'_, ConversionController>,
+) -> AppResult<ScanSummary> {
+    let cancel = state.new_scan_token().await;
+    let _permit = state.scan_limiter().acquire().await;
+
+    let options = options.unwrap_or_default();
+    let tools = get_ffmpeg_tools(&app).ok();
+    let with_probe = with_probe.unwrap_or(false) && tools.is_some();
+
+    // Probing runs next to the folder walk, so files show up before all are probed
+    let (tx, mut rx) = mpsc::unbounded_channel::<VideoFile>();
+    let emitter = {
+        let app = app.clone();
+        let tools = tools.clone();
+        let cancel = cancel.clone();
+        tauri::async_runtime::spawn(async move {
+            let mut index = 0;
+            while let Some(file) = rx.recv().await {
+                if cancel.is_cancelled() {
+                    break;
+                }
+                let probe = match tools.as_ref() {
+                    Some(t) if with_probe => {
+                        probe_video_cached(t.ffprobe.as_deref(), &t.ffmpeg, &file.path)
+                            .await
+                            .ok()
+                    }
+                    _ => None,
+                };
+                let _ = app.emit(
+                    "scan-progress",
+                    &ScanFileEvent {
+                        scan_id,
+                        index,
+                        file,
+                        probe,
+                    },
+                );
+                index += 1;
+            }
+            index
+        })
+    };
+
+    let result = scan_folder(
+        folder_path,
+        &options,
+        &output_exclude(output_folder.as_deref()),
+        tools.as_ref(),
+        Some(&tx),
+        cancel.clone(),
+    )
+    .await;
+    drop(tx);
+    let emitted = emitter.await.unwrap_or(0);
+
+    let summary = match result {
+        Ok(r) => ScanSummary {
+            scan_id,
+            found: r.files.len(),
+            duplicates: r
+                .files
+                .iter()
+                .filter_map(|f| Some((f.path.clone(), f.duplicate_of.clone()?)))
+                .collect(),
+            skipped: r.skipped,
+            chapters: r.chapters,
+            cancelled: cancel.is_cancelled(),
+            error: None,
+        },
+        Err(e) => ScanSummary {
+            scan_id,
+            found: emitted,
+            skipped: Vec::new(),
+            chapters: Vec::new(),
+            duplicates: HashMap::new(),
+            cancelled: matches!(e.code, AppErrorCode::Cancelled),
+            error: Some(e),
+        },
+    };
+    let _ = app.emit("scan-finished", &summary);
+    Ok(summary)
+}
+
+/// Stop the running streaming scan.
+#[tauri::command]
+pub async fn cancel_scan(state: State<'_, ConversionController>) -> AppResult<()> {
+    state.cancel_scan().await;
+    Ok(())
+}
+
+/// Case-insensitive natural sort: numeric segments are compared numerically,
+/// so "video_2" sorts before "video_10". Handles macOS NFD filenames correctly
+/// because numeric segments are extracted before byte comparison.
+fn natural_cmp_ignore_case(a: &str, b: &str) -> std::cmp::Ordering {
+    let a = a.to_lowercase();
+    let b = b.to_lowercase();
+    let mut ai = a.chars().peekable();
+    let mut bi = b.chars().peekable();
+
+    loop {
+        match (ai.peek().copied(), bi.peek().copied()) {
+            (None, None) => return std::cmp::Ordering::Equal,
+            (None, _) => return std::cmp::Ordering::Less,
+            (_, None) => return std::cmp::Ordering::Greater,
+            (Some(ac), Some(bc)) if ac.is_ascii_digit() && bc.is_ascii_digit() => {
+                let a_num: u64 = std::iter::from_fn(|| ai.next_if(|c| c.is_ascii_digit()))
+                    .fold(0u64, |n, c| n * 10 + (c as u64 - '0' as u64));
+                let b_num: u64 = std::iter::from_fn(|| bi.next_if(|c| c.is_ascii_digit()))
+                    .fold(0u64, |n, c| n * 10 + (c as u64 - '0' as u64));
+                match a_num.cmp(&b_num) {
+                    std::cmp::Ordering::Equal => continue,
+                    other => return other,
+                }
+            }
+            (Some(ac), Some(bc)) => {
+                ai.next();
+                bi.next();
+                match ac.cmp(&bc) {
+                    std::cmp::Ordering::Equal => continue,
+                    other => return other,
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_recursive_scan_skips_output_folder() {
+        let root = tempfile::tempdir().unwrap();
+        let base = root.path();
+        std::fs::create_dir_all(base.join("Day1/CamA/deep")).unwrap();
+        std::fs::create_dir_all(base.join("converted_videos_24fps")).unwrap();
+        for f in [
+            "top.mp4",
+            "top_24fps.partial.mp4",
+            "notes.txt",
+            "Day1/CamA/clip_10.mov",
+            "Day1/CamA/clip_2.mov",
+            "Day1/CamA/deep/far.mkv",
+            "converted_videos_24fps/top_24fps.mp4",
+        ] {
+            std::fs::write(base.join(f), b"x").unwrap();
+        }
+        let folder = base.to_string_lossy().to_string();
+
+        let flat = list_video_files(
+            folder.clone(),
+            &ScanOptions::default(),
+            &[],
+            None,
+            CancellationToken::new(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(flat.files.len(), 1);
+        assert_eq!(flat.skipped.len(), 1);
+        assert_eq!(flat.skipped[0].reason, SkipReason::UnsupportedExtension);
+
+        let options = ScanOptions {
+            recursive: true,
+            max_depth: Some(2),
+            ..ScanOptions::default()
+        };
+        let files = list_video_files(folder, &options, &[], None, CancellationToken::new())
+            .await
+            .unwrap()
+            .files;
+        let names: Vec<_> = files.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, ["top.mp4", "clip_2.mov", "clip_10.mov"]);
+        assert_eq!(
+            Path::new(&files[1].relative_dir),
+            Path::new("Day1").join("CamA")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_card_root_finds_clips() {
+        let card = tempfile::tempdir().unwrap();
+        let clips = card.path().join("PRIVATE/M4ROOT/CLIP");
+        std::fs::create_dir_all(&clips).unwrap();
+        std::fs::write(clips.join("C0001.MP4"), b"x").unwrap();
+        std::fs::write(
+            clips.join("C0001M01.XML"),
+            r#"<NonRealTimeMeta><CreationDate value="2023-05-01T12:34:56+09:00"/></NonRealTimeMeta>"#,
+        )
+        .unwrap();
+
+        let files = list_video_files(
+            card.path().to_string_lossy().to_string(),
+            &ScanOptions::default(),
+            &[],
+            None,
+            CancellationToken::new(),
+        )
+        .await
+        .unwrap()
+        .files;
+        assert_eq!(files.len(), 1);
+        let clip = files[0].clip.as_ref().unwrap();
+        assert_eq!(clip.display_name, "2023-05-01_12-34-56_C0001.MP4");
+        assert_eq!(
+            clip.creation_time.as_deref(),
+            Some("2023-05-01T03:34:56+00:00")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_duplicates_need_identical_content() {
+        let root = tempfile::tempdir().unwrap();
+        let base = root.path();
+        std::fs::create_dir_all(base.join("copy")).unwrap();
+        std::fs::write(base.join("a.mp4"), b"same bytes").unwrap();
+        std::fs::write(base.join("copy/renamed.mp4"), b"same bytes").unwrap();
+        std::fs::write(base.join("b.mp4"), b"diff bytes").unwrap();
+
+        let options = ScanOptions {
+            recursive: true,
+            detect_duplicates: true,
+            ..ScanOptions::default()
+        };
+        let files = list_video_files(
+            base.to_string_lossy().to_string(),
+            &options,
+            &[],
+            None,
+            CancellationToken::new(),
+        )
+        .await
+        .unwrap()
+        .files;
+        let dups: Vec<_> = files
+            .iter()
+            .filter_map(|f| Some((f.name.as_str(), f.duplicate_of.as_deref()?)))
+            .collect();
+        assert_eq!(dups.len(), 1);
+        assert_eq!(dups[0].0, "renamed.mp4");
+        assert!(dups[0].1.ends_with("a.mp4"));
+    }
+
+    #[tokio::test]
+    async fn test_sort_by_size_descending() {
+        let root = tempfile::tempdir().unwrap();
+        for (name, len) in [("a.mp4", 3), ("b.mp4", 10), ("c.mp4", 3), ("d.mp4", 1)] {
+            std::fs::write(root.path().join(name), vec![0u8; len]).unwrap();
+        }
+        let options = ScanOptions {
+            sort_by: SortKey::Size,
+            descending: true,
+            ..ScanOptions::default()
+        };
+        let files = list_video_files(
+            root.path().to_string_lossy().to_string(),
+            &options,
+            &[],
+            None,
+            CancellationToken::new(),
+        )
+        .await
+        .unwrap()
+        .files;
+        let names: Vec<_> = files.iter().map(|f| f.name.as_str()).collect();
+        // Equal sizes keep the name order
+        assert_eq!(names, ["b.mp4", "a.mp4", "c.mp4", "d.mp4"]);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_audio_only_file_has_no_video_stream() {
+        use crate::utils::ffmpeg::fake_tool;
+
+        let root = tempfile::tempdir().unwrap();
+        let tools_dir = tempfile::tempdir().unwrap();
+        std::fs::write(root.path().join("voice.m4a"), b"x").unwrap();
+        let ffprobe = fake_tool(
+            tools_dir.path(),
+            "ffprobe",
+            r#"#!/bin/sh
+echo '{"streams":[{"index":0,"codec_type":"audio","codec_name":"aac"}],"format":{"duration":"3.0"}}'
+"#,
+        );
+        let tools = FfmpegTools {
+            ffmpeg: fake_tool(tools_dir.path(), "ffmpeg", "#!/bin/sh\nexit 1\n"),
+            ffprobe: Some(ffprobe),
+        };
+        let options = ScanOptions {
+            extensions: vec!["m4a".into()],
+            sniff: true,
+            ..ScanOptions::default()
+        };
+
+        let result = list_video_files(
+            root.path().to_string_lossy().to_string(),
+            &options,
+            &[],
+            Some(&tools),
+            CancellationToken::new(),
+        )
+        .await
+        .unwrap();
+        assert!(result.files.is_empty());
+        assert_eq!(result.skipped.len(), 1);
+        assert!(matches!(result.skipped[0].reason, SkipReason::NoVideoStream));
+
+        // Without ffmpeg such files could not be told apart
+        let folder = root.path().to_string_lossy().to_string();
+        let err = list_video_files(folder, &options, &[], None, CancellationToken::new())
+            .await
+            .unwrap_err();
+        assert!(matches!(err.code, AppErrorCode::FfmpegNotFound));
+    }
+}
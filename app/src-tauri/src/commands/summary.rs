@@ -0,0 +1,183 @@
+// Free FPS - Video Frame Rate Converter
+// Copyright (C) 2025 undelalune
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+// Folder overview shown before a batch is started.
+
+use crate::commands::scan::{list_video_files, ScanOptions};
+use crate::commands::video::ConversionController;
+use crate::errors::{AppError, AppErrorCode, AppResult};
+use crate::utils::bundled_ffmpeg::get_ffmpeg_tools;
+use crate::utils::ffmpeg::VideoProbe;
+use crate::utils::probe_cache::probe_video_cached;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tauri::{AppHandle, State};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistogramBucket {
+    /// "29.97", "h264", "3840x2160"
+    pub label: String,
+    pub count: usize,
+    /// Seconds of footage in this bucket
+    pub duration: f64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FolderSummary {
+    pub file_count: usize,
+    pub total_size: u64,
+    /// Seconds, over the probed files
+    pub total_duration: f64,
+    /// Files that could not be probed; they are left out of durations and histograms
+    pub unprobed: usize,
+    /// Buckets sorted by count, largest first
+    pub fps: Vec<HistogramBucket>,
+    pub codecs: Vec<HistogramBucket>,
+    pub resolutions: Vec<HistogramBucket>,
+    /// Seconds of output at the target rate; frames are kept, so footage
+    /// shot at a higher rate gets longer
+    pub estimated_output_duration: Option<f64>,
+}
+
+/// "29.97", "25", "23.976"
+fn fps_label(fps: f64) -> String {
+    let s = format!("{:.3}", fps);
+    s.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+fn to_buckets(map: HashMap<String, (usize, f64)>) -> Vec<HistogramBucket> {
+    let mut buckets: Vec<HistogramBucket> = map
+        .into_iter()
+        .map(|(label, (count, duration))| HistogramBucket {
+            label,
+            count,
+            duration,
+        })
+        .collect();
+    buckets.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.label.cmp(&b.label)));
+    buckets
+}
+
+/// Aggregate the sizes and probes of the scanned files.
+fn summarize(files: &[(u64, Option<VideoProbe>)], target_fps: Option<f32>) -> FolderSummary {
+    let mut summary = FolderSummary {
+        file_count: files.len(),
+        estimated_output_duration: target_fps.map(|_| 0.0),
+        ..FolderSummary::default()
+    };
+    let mut fps = HashMap::new();
+    let mut codecs = HashMap::new();
+    let mut resolutions = HashMap::new();
+    let add = |map: &mut HashMap<String, (usize, f64)>, label: String, duration: f64| {
+        let entry = map.entry(label).or_insert((0, 0.0));
+        entry.0 += 1;
+        entry.1 += duration;
+    };
+
+    for (size, probe) in files {
+        summary.total_size += size;
+        let Some(probe) = probe else {
+            summary.unprobed += 1;
+            continue;
+        };
+        let duration = probe.duration_sec;
+        summary.total_duration += duration;
+        if let (Some(out), Some(target)) = (summary.estimated_output_duration.as_mut(), target_fps)
+        {
+            // Same formula as the conversion: every frame is kept
+            *out += duration * probe.fps / target as f64;
+        }
+        add(&mut fps, fps_label(probe.fps), duration);
+        add(
+            &mut codecs,
+            probe
+                .video_codec
+                .clone()
+                .unwrap_or_else(|| "unknown".into()),
+            duration,
+        );
+        let resolution = match (probe.width, probe.height) {
+            (Some(w), Some(h)) => format!("{}x{}", w, h),
+            _ => "unknown".into(),
+        };
+        add(&mut resolutions, resolution, duration);
+    }
+
+    summary.fps = to_buckets(fps);
+    summary.codecs = to_buckets(codecs);
+    summary.resolutions = to_buckets(resolutions);
+    summary
+}
+
+/// Scan `folder_path` like `get_video_files` and probe every file.
+/// `target_fps` enables the output duration estimate. Cancelled with `cancel_scan`.
+#[tauri::command]
+pub async fn get_folder_summary(
+    app: AppHandle,
+    folder_path: String,
+    options: Option<ScanOptions>,
+    target_fps: Option<f32>,
+    state: State<'_, ConversionController>,
+) -> AppResult<FolderSummary> {
+    let cancel = state.new_scan_token().await;
+    let _permit = state.scan_limiter().acquire().await;
+
+    let tools = get_ffmpeg_tools(&app)?;
+    let options = options.unwrap_or_default();
+    let scanned =
+        list_video_files(folder_path, &options, &[], Some(&tools), cancel.clone()).await?;
+
+    let mut files = Vec::with_capacity(scanned.files.len());
+    for f in scanned.files {
+        if cancel.is_cancelled() {
+            return Err(AppError::code_only(AppErrorCode::Cancelled));
+        }
+        let probe = probe_video_cached(tools.ffprobe.as_deref(), &tools.ffmpeg, &f.path)
+            .await
+            .ok();
+        files.push((f.size, probe));
+    }
+    Ok(summarize(&files, target_fps.filter(|f| *f > 0.0)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::ffmpeg::test_probe;
+
+    #[test]
+    fn test_summarize() {
+        let files = [
+            (100, Some(test_probe(59.94, 10.0, "hevc"))),
+            (200, Some(test_probe(59.94, 20.0, "h264"))),
+            (50, Some(test_probe(25.0, 4.0, "h264"))),
+            (7, None),
+        ];
+        let s = summarize(&files, Some(25.0));
+        assert_eq!(s.file_count, 4);
+        assert_eq!(s.total_size, 357);
+        assert_eq!(s.unprobed, 1);
+        assert!((s.total_duration - 34.0).abs() < 1e-9);
+        assert_eq!(s.fps[0].label, "59.94");
+        assert_eq!(s.fps[0].count, 2);
+        assert_eq!(s.codecs[0].label, "h264");
+        assert_eq!(s.resolutions.len(), 1);
+        let out = s.estimated_output_duration.unwrap();
+        assert!((out - (30.0 * 59.94 / 25.0 + 4.0)).abs() < 1e-6);
+
+        assert!(summarize(&files, None).estimated_output_duration.is_none());
+    }
+}
@@ -15,30 +15,107 @@
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
 use crate::errors::{AppError, AppErrorCode, AppResult};
-use crate::utils::bundled_ffmpeg::{get_ffmpeg_path, get_ffprobe_path};
-use crate::utils::ffmpeg::{convert_video_with_progress, ConvertOptions};
+use crate::utils::bundled_ffmpeg::{get_ffmpeg_path, get_ffmpeg_tools, get_ffprobe_path, FfmpegTools};
+use crate::utils::camera_card::{read_camera_clip, CameraClip};
+use crate::utils::ffmpeg::{
+    convert_video_with_progress, ConvertOptions, ConvertOutcome, VideoProbe,
+};
+use crate::utils::hashing::quick_hash;
+use crate::utils::job_queue::{self, BatchJob, JobEntry, JobFileState};
+use crate::utils::metadata_tags::{validate_tags, validate_tags_for_container, MetadataTag};
+use crate::commands::scan::{
+    default_output_dir_name, list_video_files, mark_duplicates, output_exclude, relative_dir,
+    sniff_misnamed, sort_video_files, ScanOptions, ScanResult, SortKey,
+};
+use crate::utils::probe_cache::{clear_probe_cache as clear_cache, probe_video_cached};
+use crate::utils::provenance::ConversionProvenance;
 use crate::utils::rate_limiter::RateLimiter;
+use crate::utils::sniff::container_extension;
 use chrono::{DateTime, Utc};
 use filetime::{set_file_times, FileTime};
 use open;
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
-use tauri::{AppHandle, Emitter, State};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter, Manager, State};
 use tokio::fs;
-use tokio::sync::Mutex;
+use tokio::sync::{watch, Mutex};
 use tokio_util::sync::CancellationToken;
 
 use crate::commands::thumbnail::{get_video_thumbnail_data_url};
 
-// Security: Validate that a path is within a base folder to prevent path traversal
-fn validate_safe_path(path: &str, base_folder: &str) -> AppResult<PathBuf> {
-    let base = PathBuf::from(base_folder).canonicalize().map_err(|e| {
-        AppError::new(
-            AppErrorCode::InvalidInputPath,
-            format!("Invalid base folder: {}", e),
-        )
-    })?;
+const MAX_WORKERS: u8 = 16;
+
+/// A folder a batch may read sources from.
+pub(crate) struct InputRoot {
+    pub(crate) path: String,
+    canonical: PathBuf,
+    /// Where outputs of sources below this root go
+    pub(crate) output_dir: PathBuf,
+}
 
+/// Allowed source folders of a batch: `input_roots`, or `input_folder` when
+/// none are given. When several roots share one output folder each gets its
+/// own subfolder there, so equal file names from different roots can't collide.
+pub(crate) fn resolve_input_roots(params: &VideoConversionParams) -> AppResult<Vec<InputRoot>> {
+    let paths: Vec<&String> = if params.input_roots.is_empty() {
+        vec![&params.input_folder]
+    } else {
+        params.input_roots.iter().collect()
+    };
+    let multiple = paths.len() > 1;
+
+    let mut used_names: Vec<String> = Vec::new();
+    let mut roots = Vec::with_capacity(paths.len());
+    for (i, path) in paths.into_iter().enumerate() {
+        let canonical = PathBuf::from(path).canonicalize().map_err(|e| {
+            AppError::new(
+                AppErrorCode::InvalidInputPath,
+                format!("Invalid base folder '{}': {}", path, e),
+            )
+        })?;
+        // A root inside another one would convert its files twice
+        let overlaps = |r: &&InputRoot| {
+            canonical.starts_with(&r.canonical) || r.canonical.starts_with(&canonical)
+        };
+        if let Some(other) = roots.iter().find(overlaps) {
+            return Err(AppError::new(
+                AppErrorCode::InvalidInputPath,
+                format!("Input folders '{}' and '{}' overlap", other.path, path),
+            ));
+        }
+        let subfolder = multiple.then(|| {
+            // Drive roots have no name
+            let base = canonical
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_else(|| format!("input_{}", i + 1));
+            let mut name = base.clone();
+            let mut n = 2;
+            while used_names.contains(&name) {
+                name = format!("{}_{}", base, n);
+                n += 1;
+            }
+            used_names.push(name.clone());
+            name
+        });
+        roots.push(InputRoot {
+            path: path.clone(),
+            output_dir: derive_output_folder(params, path, subfolder.as_deref()),
+            canonical,
+        });
+    }
+    Ok(roots)
+}
+
+// Security: Validate that a path is within one of the allowed roots to prevent path traversal.
+// Returns the canonical path and the most specific root containing it.
+fn validate_safe_path<'a>(
+    path: &str,
+    roots: &'a [InputRoot],
+) -> AppResult<(PathBuf, &'a InputRoot)> {
     let target = PathBuf::from(path).canonicalize().map_err(|e| {
         AppError::new(
             AppErrorCode::InvalidInputPath,
@@ -46,47 +123,67 @@ fn validate_safe_path(path: &str, base_folder: &str) -> AppResult<PathBuf> {
         )
     })?;
 
-    if !target.starts_with(&base) {
-        return Err(AppError::new(
-            AppErrorCode::PathTraversalDetected,
-            format!("Path '{}' is outside allowed directory", path),
-        ));
-    }
-
-    Ok(target)
+    let root = roots
+        .iter()
+        .filter(|r| target.starts_with(&r.canonical))
+        .max_by_key(|r| r.canonical.components().count())
+        .ok_or_else(|| {
+            AppError::new(
+                AppErrorCode::PathTraversalDetected,
+                format!("Path '{}' is outside allowed directories", path),
+            )
+        })?;
+
+    Ok((target, root))
 }
 
-// Validate conversion parameters
-fn validate_conversion_params(params: &VideoConversionParams) -> AppResult<()> {
-    if params.target_fps <= 0.0 || params.target_fps > 1000.0 {
+// Validate the settings a file is converted with
+fn validate_file_settings(settings: &FileSettings) -> AppResult<()> {
+    if settings.target_fps <= 0.0 || settings.target_fps > 1000.0 {
         return Err(AppError::new(
             AppErrorCode::InvalidFps,
             format!(
                 "FPS must be between 0.1 and 1000, got {}",
-                params.target_fps
+                settings.target_fps
             ),
         ));
     }
 
-    if params.keep_audio {
-        if params.audio_bitrate == 0 || params.audio_bitrate > 512 {
+    if settings.keep_audio {
+        if settings.audio_bitrate == 0 || settings.audio_bitrate > 512 {
             return Err(AppError::new(
                 AppErrorCode::AudioBitrateInvalid,
                 format!(
                     "Audio bitrate must be between 1 and 512 kbps, got {}",
-                    params.audio_bitrate
+                    settings.audio_bitrate
                 ),
             ));
         }
     }
 
-    if params.use_custom_video_quality && params.video_quality > 51 {
+    if settings.use_custom_video_quality && settings.video_quality > 51 {
         return Err(AppError::new(
             AppErrorCode::VideoQualityOutOfRange,
-            format!("CRF must be between 0 and 51, got {}", params.video_quality),
+            format!("CRF must be between 0 and 51, got {}", settings.video_quality),
         ));
     }
 
+    validate_tags(&settings.metadata)
+        .map_err(|e| AppError::new(AppErrorCode::InvalidMetadataTag, e))?;
+
+    Ok(())
+}
+
+// Validate conversion parameters
+pub(crate) fn validate_conversion_params(params: &VideoConversionParams) -> AppResult<()> {
+    validate_file_settings(&params.file_settings(""))?;
+    for path in params.overrides.keys() {
+        validate_file_settings(&params.file_settings(path)).map_err(|e| {
+            let details = e.details.unwrap_or_default();
+            AppError::new(e.code, format!("{}: {}", path, details))
+        })?;
+    }
+
     if params.cpu_limit == 0 || params.cpu_limit > 100 {
         return Err(AppError::new(
             AppErrorCode::Io,
@@ -97,23 +194,56 @@ fn validate_conversion_params(params: &VideoConversionParams) -> AppResult<()> {
         ));
     }
 
+    if params.workers == 0 || params.workers > MAX_WORKERS {
+        return Err(AppError::new(
+            AppErrorCode::Io,
+            format!(
+                "Workers must be between 1 and {}, got {}",
+                MAX_WORKERS, params.workers
+            ),
+        ));
+    }
+
     Ok(())
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VideoFile {
     pub path: String,
     pub name: String,
+    /// Subfolder below the scanned folder, "" for the top level
+    #[serde(default)]
+    pub relative_dir: String,
     pub size: u64,
     pub thumbnail: Option<String>,
+    /// Set when the file is already an output of Free FPS
+    #[serde(default)]
+    pub provenance: Option<ConversionProvenance>,
+    /// Set for clips found in a camera card layout
+    #[serde(default)]
+    pub clip: Option<CameraClip>,
+    /// Path of an identical file listed earlier, when duplicates were checked
+    #[serde(default)]
+    pub duplicate_of: Option<String>,
+    /// Container sniffed from the header ("matroska", "mpegts", ...), for
+    /// files taken in despite an unknown or missing extension
+    #[serde(default)]
+    pub container: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VideoConversionParams {
     pub input_folder: String,
+    /// Folders sources may come from, e.g. every folder picked in the dialog.
+    /// `input_folder` alone is used when empty.
+    #[serde(default)]
+    pub input_roots: Vec<String>,
     pub output_folder: String,
     pub target_fps: f32,
     pub cpu_limit: u8,
+    /// Files converted at the same time; the `cpu_limit` threads are split between them
+    #[serde(default = "default_workers")]
+    pub workers: u8,
     pub keep_audio: bool,
     pub audio_bitrate: u32,
     pub use_custom_video_quality: bool,
@@ -123,6 +253,80 @@ pub struct VideoConversionParams {
     pub use_gpu: bool,
     #[serde(default)]
     pub gpu_type: Option<String>,
+    /// Skip files whose output already exists and is up to date
+    #[serde(default)]
+    pub incremental: bool,
+    /// How `input_folder` is scanned when `files` is empty
+    #[serde(default)]
+    pub scan: ScanOptions,
+    /// Extra output tags; values may use template variables like `{source_name}`
+    #[serde(default)]
+    pub metadata: Vec<MetadataTag>,
+    /// Leave out files identical to one earlier in the batch, e.g. from a
+    /// card copied twice
+    #[serde(default)]
+    pub skip_duplicates: bool,
+    /// Chapter sequences (from `ScanResult::chapters`) to convert as one
+    /// output each. A sequence is only joined when all its files are part
+    /// of the batch.
+    #[serde(default)]
+    pub join_chapters: Vec<Vec<String>>,
+    /// Settings that differ from the batch for single files, by source path
+    #[serde(default)]
+    pub overrides: HashMap<String, FileOverrides>,
+    /// What to do when an output already exists
+    #[serde(default)]
+    pub on_conflict: ConflictPolicy,
+}
+
+/// Per-file replacements for the batch settings; unset fields keep the batch value.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FileOverrides {
+    pub target_fps: Option<f32>,
+    pub keep_audio: Option<bool>,
+    pub audio_bitrate: Option<u32>,
+    pub use_custom_video_quality: Option<bool>,
+    pub video_quality: Option<u8>,
+    pub use_gpu: Option<bool>,
+    pub gpu_type: Option<String>,
+    pub metadata: Option<Vec<MetadataTag>>,
+}
+
+/// Settings a file is actually converted with: the batch settings with the
+/// file's overrides applied.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileSettings {
+    pub target_fps: f32,
+    pub keep_audio: bool,
+    pub audio_bitrate: u32,
+    pub use_custom_video_quality: bool,
+    pub video_quality: u8,
+    pub use_gpu: bool,
+    pub gpu_type: Option<String>,
+    pub metadata: Vec<MetadataTag>,
+}
+
+impl VideoConversionParams {
+    /// Effective settings for the file at `path`.
+    pub fn file_settings(&self, path: &str) -> FileSettings {
+        let o = self.overrides.get(path).cloned().unwrap_or_default();
+        FileSettings {
+            target_fps: o.target_fps.unwrap_or(self.target_fps),
+            keep_audio: o.keep_audio.unwrap_or(self.keep_audio),
+            audio_bitrate: o.audio_bitrate.unwrap_or(self.audio_bitrate),
+            use_custom_video_quality: o
+                .use_custom_video_quality
+                .unwrap_or(self.use_custom_video_quality),
+            video_quality: o.video_quality.unwrap_or(self.video_quality),
+            use_gpu: o.use_gpu.unwrap_or(self.use_gpu),
+            gpu_type: o.gpu_type.or_else(|| self.gpu_type.clone()),
+            metadata: o.metadata.unwrap_or_else(|| self.metadata.clone()),
+        }
+    }
+}
+
+fn default_workers() -> u8 {
+    1
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -131,21 +335,108 @@ pub enum ConversionStatus {
     Success,
     Error,
     Cancelled,
+    /// Incremental run: the output already exists and is up to date
+    Skipped,
+    /// The conversion was paused with `pause_conversion`
+    Paused,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ConversionProgress {
     pub current_file: String,
+    /// Identifies the file when several are converted at once
+    pub file_path: String,
     pub current_file_index: usize,
     pub total_files: usize,
     pub percentage: f32,
     pub status: ConversionStatus,
+    /// Worker converting the file, 0..`workers`
+    pub worker: usize,
+    /// Effective settings of the file, sent with its status changes
+    pub settings: Option<FileSettings>,
+    /// Encoder that produced the output, sent with `Success`. "libx264" when
+    /// a failed hardware encode was redone on the CPU.
+    pub encoder: Option<String>,
+    /// What happens at the output path, sent when the file starts, ends or is skipped
+    pub output_action: Option<OutputAction>,
+    pub output_path: Option<String>,
+}
+
+/// What a batch does when a file's output already exists
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum ConflictPolicy {
+    #[default]
+    Overwrite,
+    /// Keep the existing output and don't convert the file
+    Skip,
+    /// Write next to it with a "_1", "_2", ... suffix
+    Rename,
+}
+
+/// How a file's output path was resolved
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum OutputAction {
+    Created,
+    Overwritten,
+    /// The existing output was kept and the file not converted
+    Kept,
+    /// Written under a suffixed name, since the output already existed
+    Renamed,
+}
+
+/// Output of a job with the conflict policy applied
+#[derive(Debug, Clone)]
+struct PlannedOutput {
+    /// Output path from the naming scheme
+    base: PathBuf,
+    /// Where the conversion writes; `None` when the existing output is kept
+    path: Option<PathBuf>,
+    action: OutputAction,
+}
+
+/// Apply `policy` to the output path `base`. Outputs `claimed` by earlier
+/// jobs of the batch count as existing. They are never overwritten, since
+/// both jobs would write the same partial file; such jobs are renamed.
+fn resolve_output(
+    base: &Path,
+    policy: ConflictPolicy,
+    claimed: &mut HashSet<PathBuf>,
+) -> (Option<PathBuf>, OutputAction) {
+    let taken = |p: &Path, claimed: &HashSet<PathBuf>| p.exists() || claimed.contains(p);
+    let (path, action) = if !taken(base, claimed) {
+        (Some(base.to_path_buf()), OutputAction::Created)
+    } else {
+        match policy {
+            ConflictPolicy::Overwrite if !claimed.contains(base) => {
+                (Some(base.to_path_buf()), OutputAction::Overwritten)
+            }
+            ConflictPolicy::Skip => (None, OutputAction::Kept),
+            ConflictPolicy::Overwrite | ConflictPolicy::Rename => {
+                let stem = base.file_stem().unwrap_or_default().to_string_lossy();
+                let ext = base.extension().unwrap_or_default().to_string_lossy();
+                let free = (1..)
+                    .map(|n| base.with_file_name(format!("{}_{}.{}", stem, n, ext)))
+                    .find(|p| !taken(p, claimed));
+                (free, OutputAction::Renamed)
+            }
+        }
+    };
+    if let Some(p) = &path {
+        claimed.insert(p.clone());
+    }
+    (path, action)
 }
 
 pub struct ConversionController {
     scan_limiter: RateLimiter,
     conversion_limiter: RateLimiter,
     token: Mutex<Option<CancellationToken>>,
+    /// Streaming scans have their own token, so they don't cancel conversions
+    scan_token: Mutex<Option<CancellationToken>>,
+    /// `true` while conversions are paused
+    paused: watch::Sender<bool>,
+    /// Tokens of the files in the running batch, by source path
+    file_tokens: Mutex<HashMap<String, CancellationToken>>,
 }
 
 impl ConversionController {
@@ -159,12 +450,76 @@ impl ConversionController {
         new
     }
 
+    /// Token for a new batch, which starts unpaused. Scans and thumbnails use
+    /// [`Self::new_token`] and leave the pause state alone.
+    pub async fn start_conversion(&self) -> CancellationToken {
+        self.paused.send_replace(false);
+        self.new_token().await
+    }
+
+    /// Token for a conversion started outside `convert_videos` (watch mode),
+    /// so `cancel_conversion` stops it as well.
+    pub async fn child_token(&self) -> CancellationToken {
+        let mut guard = self.token.lock().await;
+        guard.get_or_insert_with(CancellationToken::new).child_token()
+    }
+
     pub async fn cancel(&self) {
+        self.paused.send_replace(false);
         if let Some(tok) = self.token.lock().await.take() {
             tok.cancel();
         }
     }
 
+    /// Pause or resume the running conversions. Returns `false` when the
+    /// state did not change.
+    pub fn set_paused(&self, paused: bool) -> bool {
+        self.paused.send_replace(paused) != paused
+    }
+
+    pub fn pause_receiver(&self) -> watch::Receiver<bool> {
+        self.paused.subscribe()
+    }
+
+    async fn register_files(&self, files: impl IntoIterator<Item = (String, CancellationToken)>) {
+        self.file_tokens.lock().await.extend(files);
+    }
+
+    async fn unregister_files<'a>(&self, paths: impl IntoIterator<Item = &'a String>) {
+        let mut tokens = self.file_tokens.lock().await;
+        for path in paths {
+            tokens.remove(path);
+        }
+    }
+
+    /// Cancel one file of the running batch. Returns `false` when the file
+    /// is not part of it.
+    pub async fn cancel_file(&self, path: &str) -> bool {
+        match self.file_tokens.lock().await.get(path) {
+            Some(tok) => {
+                tok.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub async fn new_scan_token(&self) -> CancellationToken {
+        let mut guard = self.scan_token.lock().await;
+        if let Some(old) = guard.take() {
+            old.cancel();
+        }
+        let new = CancellationToken::new();
+        *guard = Some(new.clone());
+        new
+    }
+
+    pub async fn cancel_scan(&self) {
+        if let Some(tok) = self.scan_token.lock().await.take() {
+            tok.cancel();
+        }
+    }
+
     pub fn scan_limiter(&self) -> &RateLimiter {
         &self.scan_limiter
     }
@@ -178,6 +533,9 @@ impl Default for ConversionController {
     fn default() -> Self {
         Self {
             token: Mutex::new(None),
+            scan_token: Mutex::new(None),
+            paused: watch::channel(false).0,
+            file_tokens: Mutex::new(HashMap::new()),
             scan_limiter: RateLimiter::new(1), // Only 1 scan at a time
             conversion_limiter: RateLimiter::new(1), // Only 1 conversion at a time
         }
@@ -193,117 +551,147 @@ pub async fn get_video_thumbnail(
     get_video_thumbnail_data_url(&path, &cancel).await
 }
 
-async fn list_video_files(
-    folder_path: String,
-    cancel: CancellationToken,
-) -> AppResult<Vec<VideoFile>> {
-    use tokio::fs as async_fs;
-
-    let path = Path::new(&folder_path);
-    if !async_fs::try_exists(path).await.unwrap_or(false) {
-        return Err(AppError::code_only(AppErrorCode::FolderNotFound));
-    }
-
-    // Canonicalize the base folder for security validation
-    let base_canonical = async_fs::canonicalize(path)
+/// Probe a single file: frame rate, duration, creation time and start timecode.
+#[tauri::command]
+pub async fn get_video_probe(app: AppHandle, path: String) -> AppResult<VideoProbe> {
+    let tools = get_ffmpeg_tools(&app)?;
+    probe_video_cached(tools.ffprobe.as_deref(), &tools.ffmpeg, &path)
         .await
-        .map_err(|e| AppError::new(AppErrorCode::InvalidInputPath, e.to_string()))?;
+        .map_err(|e| AppError::new(AppErrorCode::FfprobeFailed, e))
+}
 
-    let supported_extensions = ["mp4", "mkv", "avi", "mov", "webm"];
-    let mut video_files = Vec::new();
+/// Read the provenance tag of a file converted by Free FPS.
+/// Returns `None` for files without one.
+#[tauri::command]
+pub async fn read_conversion_provenance(
+    app: AppHandle,
+    path: String,
+) -> AppResult<Option<ConversionProvenance>> {
+    let tools = get_ffmpeg_tools(&app)?;
+    let probe = probe_video_cached(tools.ffprobe.as_deref(), &tools.ffmpeg, &path)
+        .await
+        .map_err(|e| AppError::new(AppErrorCode::FfprobeFailed, e))?;
+    Ok(probe.provenance)
+}
 
-    let mut dir = async_fs::read_dir(path).await.map_err(AppError::from)?;
-    while let Some(entry) = dir.next_entry().await.map_err(AppError::from)? {
-        if cancel.is_cancelled() {
-            return Err(AppError::code_only(AppErrorCode::Cancelled));
-        }
+/// Drop all cached probe results (e.g. after files were edited in place
+/// without their modification time changing).
+#[tauri::command]
+pub async fn clear_probe_cache() -> AppResult<()> {
+    clear_cache().await.map_err(AppError::from)
+}
 
-        let file_type = entry.file_type().await.map_err(AppError::from)?;
-        if !file_type.is_file() {
-            continue;
+/// Output folder for sources below `root`: the chosen output folder (plus
+/// `root_subfolder` in multi-root batches), or a folder inside the root.
+pub(crate) fn derive_output_folder(
+    params: &VideoConversionParams,
+    root: &str,
+    root_subfolder: Option<&str>,
+) -> PathBuf {
+    if !params.output_folder.trim().is_empty() {
+        let mut p = PathBuf::from(&params.output_folder);
+        if let Some(sub) = root_subfolder {
+            p.push(sub);
         }
+        return p;
+    }
+    let mut p = PathBuf::from(root);
+    p.push(default_output_dir_name(params.target_fps));
+    p
+}
 
-        let file_path = entry.path();
-
-        // Security: Ensure file is within the base folder
-        if let Ok(canonical) = async_fs::canonicalize(&file_path).await {
-            if !canonical.starts_with(&base_canonical) {
-                continue; // Skip files outside base folder
-            }
-        } else {
-            continue; // Skip files that can't be canonicalized
-        }
+/// Output file for `video_file`: "<stem>_<fps>fps.<ext>", placed in the same
+/// subfolder below the root's output folder as the source has below the root.
+fn derive_output_path(root: &InputRoot, video_file: &VideoFile, target_fps: f32) -> PathBuf {
+    let input_path = Path::new(&video_file.path);
+    // Card clips are named after their recording time and leave the card's
+    // folder structure behind
+    let (named, rel_dir) = match &video_file.clip {
+        Some(clip) => (Path::new(&clip.display_name), String::new()),
+        None => (input_path, relative_dir(input_path, Path::new(&root.path))),
+    };
+    let output_filename = format!(
+        "{}_{}fps.{}",
+        named.file_stem().unwrap_or_default().to_string_lossy(),
+        target_fps,
+        output_extension(video_file)
+    );
+    root.output_dir.join(rel_dir).join(output_filename)
+}
 
-        let extension = file_path
+/// Outputs keep the source container. Sniffed files get the extension of
+/// their real container, since ffmpeg picks the muxer from it.
+fn output_extension(video_file: &VideoFile) -> String {
+    match &video_file.container {
+        Some(container) => container_extension(container).to_string(),
+        None => Path::new(&video_file.path)
             .extension()
-            .and_then(|e| e.to_str())
-            .map(|e| e.to_lowercase());
-
-        if let Some(ext) = extension {
-            if supported_extensions.contains(&ext.as_str()) {
-                let metadata = entry.metadata().await.map_err(AppError::from)?;
-                video_files.push(VideoFile {
-                    path: file_path.to_string_lossy().to_string(),
-                    name: file_path
-                        .file_name()
-                        .unwrap_or_default()
-                        .to_string_lossy()
-                        .to_string(),
-                    size: metadata.len(),
-                    thumbnail: None,
-                });
-            }
-        }
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string(),
     }
+}
 
-    video_files.sort_by(|a, b| natural_cmp_ignore_case(&a.name, &b.name));
-
-    Ok(video_files)
-}
-
-/// Case-insensitive natural sort: numeric segments are compared numerically,
-/// so "video_2" sorts before "video_10". Handles macOS NFD filenames correctly
-/// because numeric segments are extracted before byte comparison.
-fn natural_cmp_ignore_case(a: &str, b: &str) -> std::cmp::Ordering {
-    let a = a.to_lowercase();
-    let b = b.to_lowercase();
-    let mut ai = a.chars().peekable();
-    let mut bi = b.chars().peekable();
-
-    loop {
-        match (ai.peek().copied(), bi.peek().copied()) {
-            (None, None) => return std::cmp::Ordering::Equal,
-            (None, _) => return std::cmp::Ordering::Less,
-            (_, None) => return std::cmp::Ordering::Greater,
-            (Some(ac), Some(bc)) if ac.is_ascii_digit() && bc.is_ascii_digit() => {
-                let a_num: u64 = std::iter::from_fn(|| ai.next_if(|c| c.is_ascii_digit()))
-                    .fold(0u64, |n, c| n * 10 + (c as u64 - '0' as u64));
-                let b_num: u64 = std::iter::from_fn(|| bi.next_if(|c| c.is_ascii_digit()))
-                    .fold(0u64, |n, c| n * 10 + (c as u64 - '0' as u64));
-                match a_num.cmp(&b_num) {
-                    std::cmp::Ordering::Equal => continue,
-                    other => return other,
-                }
-            }
-            (Some(ac), Some(bc)) => {
-                ai.next();
-                bi.next();
-                match ac.cmp(&bc) {
-                    std::cmp::Ordering::Equal => continue,
-                    other => return other,
-                }
+/// Pair every input with the chapters joined after it. Later chapters of a
+/// joined sequence are dropped from the batch.
+fn plan_jobs(inputs: Vec<VideoFile>, joins: &[Vec<String>]) -> Vec<(VideoFile, Vec<String>)> {
+    let paths: HashSet<&str> = inputs.iter().map(|f| f.path.as_str()).collect();
+    let mut rest_of: HashMap<String, Vec<String>> = HashMap::new();
+    for group in joins {
+        let complete = group.iter().all(|p| paths.contains(p.as_str()));
+        if let (true, [first, rest @ ..]) = (complete, group.as_slice()) {
+            if !rest.is_empty() {
+                rest_of.insert(first.clone(), rest.to_vec());
             }
         }
     }
+    let later: HashSet<String> = rest_of.values().flatten().cloned().collect();
+    inputs
+        .into_iter()
+        .filter(|f| !later.contains(&f.path))
+        .map(|f| {
+            let rest = rest_of.remove(&f.path).unwrap_or_default();
+            (f, rest)
+        })
+        .collect()
 }
 
-fn derive_output_folder(params: &VideoConversionParams) -> PathBuf {
-    if !params.output_folder.trim().is_empty() {
-        return PathBuf::from(&params.output_folder);
+/// Incremental runs skip a source when its output probes as a finished
+/// conversion at the target rate and its provenance tag shows it is current:
+/// the conversion started after the source was last modified, or the tag
+/// names this exact source. Outputs carry the source's file times (see
+/// `convert_job`), so the output's own mtime says nothing here.
+async fn is_already_converted(
+    source: &Path,
+    output: &Path,
+    target_fps: f32,
+    ffprobe_bin: Option<&str>,
+    ffmpeg_bin: &str,
+) -> bool {
+    let (Ok(src_meta), Ok(out_meta)) = (fs::metadata(source).await, fs::metadata(output).await)
+    else {
+        return false;
+    };
+    if out_meta.len() == 0 {
+        return false;
     }
-    let mut p = PathBuf::from(&params.input_folder);
-    p.push(format!("converted_videos_{}fps", params.target_fps));
-    p
+
+    // Sanity check: a readable video at the target rate with a real duration
+    let Ok(probe) = probe_video_cached(ffprobe_bin, ffmpeg_bin, &output.to_string_lossy()).await
+    else {
+        return false;
+    };
+    if (probe.fps - target_fps as f64).abs() > 0.01 || probe.duration_sec <= 0.0 {
+        return false;
+    }
+
+    let Some(provenance) = probe.provenance else {
+        return false;
+    };
+    if src_meta.modified().is_ok_and(|m| provenance.newer_than(m)) {
+        return true;
+    }
+    quick_hash(source).await.is_ok_and(|h| provenance.matches_source(&h))
 }
 
 fn parse_creation_time(ct: &str) -> Option<std::time::SystemTime> {
@@ -378,14 +766,21 @@ fn set_creation_time_windows(path: &Path, t: std::time::SystemTime) -> Result<()
 
 #[tauri::command]
 pub async fn get_video_files(
+    app: AppHandle,
     folder_path: String,
+    options: Option<ScanOptions>,
+    output_folder: Option<String>,
     state: State<'_, ConversionController>,
-) -> AppResult<Vec<VideoFile>> {
+) -> AppResult<ScanResult> {
     // Rate limiting: Only one scan at a time
     let _permit = state.scan_limiter().acquire().await;
 
     let cancel = state.new_token().await;
-    list_video_files(folder_path, cancel).await
+    let tools = get_ffmpeg_tools(&app).ok();
+    let options = options.unwrap_or_default();
+    // Earlier outputs in a custom output folder inside the input folder are not sources
+    let exclude = output_exclude(output_folder.as_deref());
+    list_video_files(folder_path, &options, &exclude, tools.as_ref(), cancel).await
 }
 
 #[tauri::command]
@@ -396,16 +791,339 @@ pub async fn convert_videos(
 ) -> AppResult<String> {
     // Rate limiting: Only one conversion at a time
     let _permit = state.conversion_limiter().acquire().await;
-    let cancel = state.new_token().await;
+    let cancel = state.start_conversion().await;
 
+    let summary = run_conversion(&app, &params, cancel, RunMode::Batch).await?;
+    Ok(format!("Successfully converted {} videos", summary.total))
+}
+
+/// Batch left unfinished by a crash or by closing the app, if any. Partial
+/// outputs of the files that were being converted were deleted at startup.
+#[tauri::command]
+pub async fn get_interrupted_job() -> AppResult<Option<BatchJob>> {
+    Ok(job_queue::load_interrupted().await)
+}
+
+/// Continue an interrupted batch with its unfinished files.
+#[tauri::command]
+pub async fn resume_interrupted_job(
+    app: AppHandle,
+    state: State<'_, ConversionController>,
+) -> AppResult<String> {
+    let _permit = state.conversion_limiter().acquire().await;
+    let Some(job) = job_queue::load_interrupted().await else {
+        return Err(AppError::code_only(AppErrorCode::NoVideoFiles));
+    };
+    let cancel = state.start_conversion().await;
+
+    let summary = run_conversion(&app, &job.resume_params(), cancel, RunMode::Batch).await?;
+    Ok(format!("Successfully converted {} videos", summary.total))
+}
+
+#[tauri::command]
+pub async fn discard_interrupted_job() -> AppResult<()> {
+    job_queue::discard().await;
+    Ok(())
+}
+
+/// How [`run_conversion`] treats a batch
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RunMode {
+    /// Started by the user: journaled for resuming and the output folder is opened at the end
+    Batch,
+    /// Single files picked up by watch mode
+    Watch,
+}
+
+/// A batch shared by its conversion workers
+struct Batch {
+    app: AppHandle,
+    params: VideoConversionParams,
+    roots: Vec<InputRoot>,
+    ffmpeg: String,
+    ffprobe: Option<String>,
+    /// Sources with the chapters joined after them, in batch order
+    jobs: Vec<(VideoFile, Vec<String>)>,
+    /// Effective settings of each job
+    settings: Vec<FileSettings>,
+    /// Output of each job; `None` when its source is outside the input roots
+    outputs: Vec<Option<PlannedOutput>>,
+    workers: usize,
+    /// Next job to hand out
+    next: AtomicUsize,
+    cancel: CancellationToken,
+    /// Per-job children of `cancel`, cancelled to skip a single file
+    file_cancel: Vec<CancellationToken>,
+    pause: watch::Receiver<bool>,
+    /// Record per-file states in the job journal
+    journal: bool,
+}
+
+impl Batch {
+    /// Event for the job at `index`, without output details
+    fn progress(
+        &self,
+        index: usize,
+        worker: usize,
+        percentage: f32,
+        status: ConversionStatus,
+    ) -> ConversionProgress {
+        let (video_file, _) = &self.jobs[index];
+        ConversionProgress {
+            current_file: video_file.name.clone(),
+            file_path: video_file.path.clone(),
+            current_file_index: index + 1,
+            total_files: self.jobs.len(),
+            percentage,
+            status,
+            worker,
+            settings: Some(self.settings[index].clone()),
+            encoder: None,
+            output_action: None,
+            output_path: None,
+        }
+    }
+
+    fn send(&self, progress: &ConversionProgress) {
+        let _ = self.app.emit("conversion-progress", progress);
+    }
+
+    fn emit(&self, index: usize, worker: usize, percentage: f32, status: ConversionStatus) {
+        self.send(&self.progress(index, worker, percentage, status));
+    }
+}
+
+/// Take jobs in batch order until none are left or the batch is cancelled.
+async fn conversion_worker(batch: Arc<Batch>, worker: usize) -> AppResult<ConversionSummary> {
+    let mut summary = ConversionSummary::default();
+    let mut pause = batch.pause.clone();
+    while !batch.cancel.is_cancelled() {
+        // Don't start the next file while paused
+        tokio::select! {
+            _ = batch.cancel.cancelled() => break,
+            _ = pause.wait_for(|paused| !paused) => {}
+        }
+        let index = batch.next.fetch_add(1, Ordering::Relaxed);
+        if index >= batch.jobs.len() {
+            break;
+        }
+        let result = convert_job(&batch, index, worker).await;
+        if batch.journal {
+            let state = match &result {
+                Ok(ConversionStatus::Success) => JobFileState::Done,
+                Ok(ConversionStatus::Skipped) => JobFileState::Skipped,
+                Ok(_) => JobFileState::Failed,
+                // Converted again on resume
+                Err(_) => JobFileState::Pending,
+            };
+            job_queue::set_state(index, state).await;
+        }
+        match result {
+            Ok(ConversionStatus::Success) => summary.succeeded += 1,
+            Ok(ConversionStatus::Skipped) => summary.skipped += 1,
+            Ok(_) => summary.failed += 1,
+            Err(e) => {
+                if !matches!(e.code, AppErrorCode::Cancelled) {
+                    batch.cancel.cancel();
+                }
+                return Err(e);
+            }
+        }
+    }
+    Ok(summary)
+}
+
+/// Convert one job and report it. Returns the file's final status; errors
+/// end the whole batch.
+async fn convert_job(batch: &Batch, index: usize, worker: usize) -> AppResult<ConversionStatus> {
+    let params = &batch.params;
+    let (video_file, chapters) = &batch.jobs[index];
+    let file_cancel = &batch.file_cancel[index];
+    let settings = &batch.settings[index];
+
+    // Skipped by the user while queued
+    if file_cancel.is_cancelled() && !batch.cancel.is_cancelled() {
+        batch.emit(index, worker, 0.0, ConversionStatus::Skipped);
+        return Ok(ConversionStatus::Skipped);
+    }
+
+    // Security: Validate the file path is within one of the input roots
+    let planned = match validate_safe_path(&video_file.path, &batch.roots) {
+        Ok(_) => batch.outputs[index].as_ref(),
+        Err(e) => {
+            eprintln!("Path validation failed for {}: {:?}", video_file.path, e);
+            None
+        }
+    };
+    let Some(planned) = planned else {
+        batch.emit(index, worker, 0.0, ConversionStatus::Error);
+        return Ok(ConversionStatus::Error);
+    };
+    let report = |percentage, status, path: &Path, action| ConversionProgress {
+        output_action: Some(action),
+        output_path: Some(path.to_string_lossy().to_string()),
+        ..batch.progress(index, worker, percentage, status)
+    };
+    let input_path = Path::new(&video_file.path);
+    let chapters_ok = chapters
+        .iter()
+        .all(|c| validate_safe_path(c, &batch.roots).is_ok() && Path::new(c).is_file());
+
+    if !input_path.is_file() || !chapters_ok {
+        batch.emit(index, worker, 0.0, ConversionStatus::Error);
+        return Ok(ConversionStatus::Error);
+    }
+
+    if params.incremental
+        && is_already_converted(
+            input_path,
+            &planned.base,
+            settings.target_fps,
+            batch.ffprobe.as_deref(),
+            &batch.ffmpeg,
+        )
+        .await
+    {
+        let kept = report(100.0, ConversionStatus::Skipped, &planned.base, OutputAction::Kept);
+        batch.send(&kept);
+        return Ok(ConversionStatus::Skipped);
+    }
+
+    // The conflict policy keeps the existing output
+    let Some(output_path) = &planned.path else {
+        let kept = report(100.0, ConversionStatus::Skipped, &planned.base, OutputAction::Kept);
+        batch.send(&kept);
+        return Ok(ConversionStatus::Skipped);
+    };
+
+    batch.send(&report(
+        0.0,
+        ConversionStatus::Processing,
+        output_path,
+        planned.action,
+    ));
+    if batch.journal {
+        job_queue::set_state(index, JobFileState::Running).await;
+    }
+
+    if let Some(parent) = output_path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| AppError::new(AppErrorCode::Io, e.to_string()))?;
+    }
+
+    let progress = ConversionProgress {
+        settings: None,
+        ..batch.progress(index, worker, 0.0, ConversionStatus::Processing)
+    };
+    let app_clone = batch.app.clone();
+    let cancel_clone = file_cancel.clone();
+
+    let convert_res = convert_video_with_progress(
+        ConvertOptions {
+            ffmpeg_bin: &batch.ffmpeg,
+            ffprobe_bin: batch.ffprobe.as_deref(),
+            input: &video_file.path,
+            output: &output_path.to_string_lossy(),
+            target_fps: settings.target_fps,
+            keep_audio: settings.keep_audio,
+            audio_bitrate: settings.audio_bitrate,
+            use_custom_video_quality: settings.use_custom_video_quality,
+            video_quality: settings.video_quality,
+            cpu_limit: Some(params.cpu_limit),
+            workers: batch.workers,
+            use_gpu: settings.use_gpu,
+            gpu_type: settings.gpu_type.clone(),
+            metadata: &settings.metadata,
+            creation_time: video_file
+                .clip
+                .as_ref()
+                .and_then(|c| c.creation_time.as_deref()),
+            chapters,
+            pause: Some(batch.pause.clone()),
+        },
+        move |p| {
+            if cancel_clone.is_cancelled() {
+                return;
+            }
+            let detailed = ConversionProgress {
+                percentage: p.clamp(0.0, 100.0),
+                ..progress.clone()
+            };
+            let _ = app_clone.emit("conversion-progress", &detailed);
+        },
+        file_cancel.clone(),
+    )
+    .await;
+
+    match convert_res {
+        Ok(ConvertOutcome {
+            creation_time,
+            encoder,
+        }) => {
+            let ts_sys = if let Some(ct) = creation_time.as_deref() {
+                parse_creation_time(ct)
+            } else {
+                fs::metadata(&video_file.path)
+                    .await
+                    .ok()
+                    .and_then(|m| m.modified().ok())
+            };
+            if let Some(ts) = ts_sys {
+                let ft = FileTime::from_system_time(ts);
+                let _ = set_file_times(output_path, ft, ft);
+                #[cfg(target_os = "windows")]
+                {
+                    let _ = set_creation_time_windows(output_path, ts);
+                }
+            }
+
+            batch.send(&ConversionProgress {
+                encoder: Some(encoder),
+                ..report(100.0, ConversionStatus::Success, output_path, planned.action)
+            });
+            Ok(ConversionStatus::Success)
+        }
+        Err(e) if e == "Cancelled" && !batch.cancel.is_cancelled() => {
+            // Skipped by the user while converting; the partial output is already gone
+            batch.emit(index, worker, 0.0, ConversionStatus::Skipped);
+            Ok(ConversionStatus::Skipped)
+        }
+        Err(e) if e == "Cancelled" => Err(AppError::code_only(AppErrorCode::Cancelled)),
+        Err(_) => {
+            batch.emit(index, worker, 0.0, ConversionStatus::Error);
+            Ok(ConversionStatus::Error)
+        }
+    }
+}
+
+/// Outcome of a [`run_conversion`] batch.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ConversionSummary {
+    pub total: usize,
+    pub succeeded: usize,
+    pub skipped: usize,
+    pub failed: usize,
+}
+
+/// Convert a batch and emit `conversion-progress` events. Shared by
+/// `convert_videos` and watch mode; callers hold the conversion permit.
+pub async fn run_conversion(
+    app: &AppHandle,
+    params: &VideoConversionParams,
+    cancel: CancellationToken,
+    mode: RunMode,
+) -> AppResult<ConversionSummary> {
     // Validate parameters first
-    validate_conversion_params(&params)?;
+    validate_conversion_params(params)?;
 
     // Get bundled FFmpeg paths
-    let ffmpeg_bin = get_ffmpeg_path(&app)?;
-    let ffprobe_bin = get_ffprobe_path(&app).ok();
+    let ffmpeg_bin = get_ffmpeg_path(app)?;
+    let ffprobe_bin = get_ffprobe_path(app).ok();
+
+    let roots = resolve_input_roots(params)?;
 
-    let inputs: Vec<VideoFile> = if !params.files.is_empty() {
+    // `duplicates_marked` is set when the scan below already checked for duplicates
+    let (inputs, duplicates_marked) = if !params.files.is_empty() {
         let mut video_files = Vec::new();
         for p in &params.files {
             let pb = PathBuf::from(p);
@@ -418,209 +1136,399 @@ pub async fn convert_videos(
             video_files.push(VideoFile {
                 path: pb.to_string_lossy().to_string(),
                 name,
+                // Resolved against the file's root when its output path is derived
+                relative_dir: String::new(),
                 size,
                 thumbnail: None,
+                provenance: None,
+                clip: read_camera_clip(&pb).await,
+                duplicate_of: None,
+                container: sniff_misnamed(&pb).await,
             });
         }
-        video_files
+        (video_files, false)
     } else {
-        list_video_files(params.input_folder.clone(), cancel.clone()).await?
+        // Never pick up earlier outputs when an output folder is inside an input folder
+        let exclude: Vec<PathBuf> = roots
+            .iter()
+            .filter_map(|r| r.output_dir.canonicalize().ok())
+            .collect();
+        let tools = FfmpegTools {
+            ffmpeg: ffmpeg_bin.to_string_lossy().to_string(),
+            ffprobe: ffprobe_bin.as_ref().map(|p| p.to_string_lossy().to_string()),
+        };
+        // A single root's scan finds its duplicates; across roots they are
+        // checked once the whole batch is listed
+        let scan = ScanOptions {
+            detect_duplicates: roots.len() == 1
+                && (params.scan.detect_duplicates || params.skip_duplicates),
+            ..params.scan.clone()
+        };
+        let mut video_files = Vec::new();
+        for root in &roots {
+            let scanned = list_video_files(
+                root.path.clone(),
+                &scan,
+                &exclude,
+                // Probing is only needed to drop non-video or filtered files, or to sort
+                (params.scan.sniff
+                    || params.scan.filter.needs_probe()
+                    || params.scan.sort_by.needs_probe())
+                .then_some(&tools),
+                cancel.clone(),
+            )
+            .await?;
+            video_files.extend(scanned.files);
+        }
+        // Each root comes sorted; other keys than the name order the whole batch
+        if roots.len() > 1 && params.scan.sort_by != SortKey::Name {
+            sort_video_files(&mut video_files, &params.scan, Some(&tools)).await;
+        }
+        (video_files, scan.detect_duplicates)
     };
 
     if inputs.is_empty() {
         return Err(AppError::code_only(AppErrorCode::NoVideoFiles));
     }
 
-    let output_dir = derive_output_folder(&params);
-    std::fs::create_dir_all(&output_dir)
-        .map_err(|e| AppError::new(AppErrorCode::Io, e.to_string()))?;
-
-    let total_files = inputs.len();
-
-    for (index, video_file) in inputs.iter().enumerate() {
-        if cancel.is_cancelled() {
-            let cancelled = ConversionProgress {
-                current_file: String::new(),
-                current_file_index: index,
-                total_files,
-                percentage: 0.0,
-                status: ConversionStatus::Cancelled,
-            };
-            let _ = app.emit("conversion-progress", &cancelled);
-            return Err(AppError::code_only(AppErrorCode::Cancelled));
-        }
-
-        // Security: Validate the file path is within input folder
-        match validate_safe_path(&video_file.path, &params.input_folder) {
-            Ok(p) => p,
-            Err(e) => {
-                let err_evt = ConversionProgress {
-                    current_file: video_file.name.clone(),
-                    current_file_index: index + 1,
-                    total_files,
-                    percentage: 0.0,
-                    status: ConversionStatus::Error,
-                };
-                let _ = app.emit("conversion-progress", &err_evt);
-                eprintln!("Path validation failed for {}: {:?}", video_file.path, e);
-                continue;
-            }
-        };
-        let input_path = Path::new(&video_file.path);
-
-        if !input_path.is_file() {
-            let err_evt = ConversionProgress {
-                current_file: video_file.name.clone(),
-                current_file_index: index + 1,
-                total_files,
-                percentage: 0.0,
-                status: ConversionStatus::Error,
-            };
-            let _ = app.emit("conversion-progress", &err_evt);
-            continue;
+    let mut inputs = inputs;
+    if params.skip_duplicates {
+        if !duplicates_marked {
+            mark_duplicates(&mut inputs, &cancel).await?;
         }
+        inputs.retain(|f| f.duplicate_of.is_none());
+    }
 
-        let progress = ConversionProgress {
-            current_file: video_file.name.clone(),
-            current_file_index: index + 1,
-            total_files,
-            percentage: 0.0,
-            status: ConversionStatus::Processing,
-        };
-        app.emit("conversion-progress", &progress)
-            .map_err(|e| AppError::new(AppErrorCode::Io, e.to_string()))?;
+    let jobs = plan_jobs(inputs, &params.join_chapters);
+    let settings: Vec<FileSettings> = jobs
+        .iter()
+        .map(|(video_file, _)| params.file_settings(&video_file.path))
+        .collect();
+
+    // Outputs keep the source container, so check tags against every input's extension
+    for ((video_file, _), settings) in jobs.iter().zip(&settings) {
+        let ext = output_extension(video_file);
+        validate_tags_for_container(&settings.metadata, &ext)
+            .map_err(|e| AppError::new(AppErrorCode::InvalidMetadataTag, e))?;
+    }
 
-        let output_filename = format!(
-            "{}_{}fps.{}",
-            input_path.file_stem().unwrap().to_string_lossy(),
-            params.target_fps,
-            input_path.extension().unwrap_or_default().to_string_lossy()
-        );
-        let output_path = output_dir.join(output_filename);
-
-        let app_clone = app.clone();
-        let video_name = video_file.name.clone();
-        let current_index = index;
-        let total = total_files;
-        let cancel_clone = cancel.clone();
-
-        let ffmpeg_str = ffmpeg_bin.to_string_lossy().to_string();
-        let ffprobe_str = ffprobe_bin.as_ref().map(|p| p.to_string_lossy().to_string());
-
-        let convert_res = convert_video_with_progress(
-            ConvertOptions {
-                ffmpeg_bin: &ffmpeg_str,
-                ffprobe_bin: ffprobe_str.as_deref(),
-                input: &video_file.path,
-                output: &output_path.to_string_lossy(),
-                target_fps: params.target_fps,
-                keep_audio: params.keep_audio,
-                audio_bitrate: params.audio_bitrate,
-                use_custom_video_quality: params.use_custom_video_quality,
-                video_quality: params.video_quality,
-                cpu_limit: Some(params.cpu_limit),
-                use_gpu: params.use_gpu,
-                gpu_type: params.gpu_type.clone(),
-            },
-            move |p| {
-                if cancel_clone.is_cancelled() {
-                    return;
-                }
-                let p01 = (p / 100.0).clamp(0.0, 1.0);
-                let file_pct = p01 * 100.0;
-
-                let detailed = ConversionProgress {
-                    current_file: video_name.clone(),
-                    current_file_index: current_index + 1,
-                    total_files: total,
-                    percentage: file_pct,
-                    status: ConversionStatus::Processing,
-                };
-                let _ = app_clone.emit("conversion-progress", &detailed);
-            },
-            cancel.clone(),
+    // Output paths, with the conflict policy applied in batch order
+    let mut claimed = HashSet::new();
+    let outputs: Vec<Option<PlannedOutput>> = jobs
+        .iter()
+        .zip(&settings)
+        .map(|((video_file, _), settings)| {
+            let (_, root) = validate_safe_path(&video_file.path, &roots).ok()?;
+            let base = derive_output_path(root, video_file, settings.target_fps);
+            let (path, action) = resolve_output(&base, params.on_conflict, &mut claimed);
+            Some(PlannedOutput { base, path, action })
+        })
+        .collect();
+
+    let total_files = jobs.len();
+    let workers = (params.workers as usize).min(total_files);
+    // A failing worker stops the others without looking like a user cancel
+    let batch_cancel = cancel.child_token();
+    let file_cancel: Vec<CancellationToken> =
+        jobs.iter().map(|_| batch_cancel.child_token()).collect();
+    let controller = app.state::<ConversionController>();
+    controller
+        .register_files(
+            jobs.iter()
+                .map(|(f, _)| f.path.clone())
+                .zip(file_cancel.iter().cloned()),
         )
-            .await;
-
-        match convert_res {
-            Ok(creation_time_str) => {
-                let ts_sys = if let Some(ct) = creation_time_str.as_deref() {
-                    parse_creation_time(ct)
-                } else {
-                    fs::metadata(&video_file.path)
-                        .await
-                        .ok()
-                        .and_then(|m| m.modified().ok())
-                };
-                if let Some(ts) = ts_sys {
-                    let ft = FileTime::from_system_time(ts);
-                    let _ = set_file_times(&output_path, ft, ft);
-                    #[cfg(target_os = "windows")]
-                    {
-                        let _ = set_creation_time_windows(&output_path, ts);
-                    }
-                }
+        .await;
+    let batch = Arc::new(Batch {
+        app: app.clone(),
+        params: params.clone(),
+        roots,
+        ffmpeg: ffmpeg_bin.to_string_lossy().to_string(),
+        ffprobe: ffprobe_bin.as_ref().map(|p| p.to_string_lossy().to_string()),
+        jobs,
+        settings,
+        outputs,
+        workers,
+        next: AtomicUsize::new(0),
+        cancel: batch_cancel.clone(),
+        file_cancel,
+        pause: controller.pause_receiver(),
+        journal: mode == RunMode::Batch,
+    });
+
+    if batch.journal {
+        let entries = batch
+            .jobs
+            .iter()
+            .zip(&batch.outputs)
+            .map(|((video_file, chapters), planned)| JobEntry {
+                source: video_file.path.clone(),
+                chapters: chapters.clone(),
+                output: planned
+                    .as_ref()
+                    .map(|o| o.path.as_ref().unwrap_or(&o.base).to_string_lossy().to_string())
+                    .unwrap_or_default(),
+                state: JobFileState::Pending,
+            })
+            .collect();
+        job_queue::begin(BatchJob::new(params.clone(), entries)).await;
+    }
 
-                let done = ConversionProgress {
-                    current_file: video_file.name.clone(),
-                    current_file_index: index + 1,
-                    total_files,
-                    percentage: 100.0,
-                    status: ConversionStatus::Success,
-                };
-                let _ = app.emit("conversion-progress", &done);
+    let handles: Vec<_> = (0..workers)
+        .map(|worker| tauri::async_runtime::spawn(conversion_worker(batch.clone(), worker)))
+        .collect();
+    let mut summary = ConversionSummary {
+        total: total_files,
+        ..ConversionSummary::default()
+    };
+    let mut error = None;
+    for handle in handles {
+        match handle.await {
+            Ok(Ok(s)) => {
+                summary.succeeded += s.succeeded;
+                summary.skipped += s.skipped;
+                summary.failed += s.failed;
+            }
+            Ok(Err(e)) if matches!(e.code, AppErrorCode::Cancelled) => {}
+            Ok(Err(e)) => {
+                error.get_or_insert(e);
             }
             Err(e) => {
-                if e == "Cancelled" {
-                    let _ = app.emit(
-                        "conversion-progress",
-                        &ConversionProgress {
-                            current_file: String::new(),
-                            current_file_index: index,
-                            total_files,
-                            percentage: 0.0,
-                            status: ConversionStatus::Cancelled,
-                        },
-                    );
-                    return Err(AppError::code_only(AppErrorCode::Cancelled));
-                }
-
-                let err_evt = ConversionProgress {
-                    current_file: video_file.name.clone(),
-                    current_file_index: index + 1,
-                    total_files,
-                    percentage: 0.0,
-                    status: ConversionStatus::Error,
-                };
-                let _ = app.emit("conversion-progress", &err_evt);
-                continue;
+                error.get_or_insert(AppError::new(AppErrorCode::Io, e.to_string()));
             }
         }
     }
+    controller
+        .unregister_files(batch.jobs.iter().map(|(f, _)| &f.path))
+        .await;
+    if batch.journal {
+        job_queue::finish().await;
+    }
+    if let Some(e) = error {
+        return Err(e);
+    }
+    if cancel.is_cancelled() {
+        let _ = app.emit(
+            "conversion-progress",
+            &ConversionProgress {
+                current_file: String::new(),
+                file_path: String::new(),
+                current_file_index: batch.next.load(Ordering::Relaxed).min(total_files),
+                total_files,
+                percentage: 0.0,
+                status: ConversionStatus::Cancelled,
+                worker: 0,
+                settings: None,
+                encoder: None,
+                output_action: None,
+                output_path: None,
+            },
+        );
+        return Err(AppError::code_only(AppErrorCode::Cancelled));
+    }
 
-    if let Err(e) = open::that(&output_dir) {
-        eprintln!("Failed to open file manager: {}", e);
+    if mode == RunMode::Batch {
+        // A common destination, or the first root's output folder
+        if let Some(root) = batch.roots.iter().find(|r| r.output_dir.is_dir()) {
+            if let Err(e) = open::that(&root.output_dir) {
+                eprintln!("Failed to open file manager: {}", e);
+            }
+        }
     }
 
-    Ok(format!("Successfully converted {} videos", total_files))
+    Ok(summary)
 }
 
-#[tauri::command]
-pub async fn cancel_conversion(
-    app: AppHandle,
-    state: State<'_, ConversionController>,
-) -> AppResult<()> {
-    state.cancel().await;
+/// Emit a file-less progress event carrying only a status
+fn emit_batch_status(app: &AppHandle, status: ConversionStatus) -> AppResult<()> {
     app.emit(
         "conversion-progress",
         &ConversionProgress {
             current_file: String::new(),
+            file_path: String::new(),
             current_file_index: 0,
             total_files: 0,
             percentage: 0.0,
-            status: ConversionStatus::Cancelled,
+            status,
+            worker: 0,
+            settings: None,
+            encoder: None,
+            output_action: None,
+            output_path: None,
         },
     )
-        .map_err(|e| AppError::new(AppErrorCode::Io, e.to_string()))?;
+    .map_err(|e| AppError::new(AppErrorCode::Io, e.to_string()))
+}
+
+#[tauri::command]
+pub async fn cancel_conversion(
+    app: AppHandle,
+    state: State<'_, ConversionController>,
+) -> AppResult<()> {
+    state.cancel().await;
+    emit_batch_status(&app, ConversionStatus::Cancelled)
+}
+
+/// Skip one file of the running batch: a queued file is not converted, a
+/// running one is stopped and its partial output removed. The rest of the
+/// batch continues. Returns `false` when the file is not in the batch.
+#[tauri::command]
+pub async fn cancel_file_conversion(
+    file_path: String,
+    state: State<'_, ConversionController>,
+) -> AppResult<bool> {
+    Ok(state.cancel_file(&file_path).await)
+}
+
+/// Suspend the running ffmpeg processes and hold back the next files.
+/// Time spent paused does not count towards the conversion timeout.
+#[tauri::command]
+pub async fn pause_conversion(
+    app: AppHandle,
+    state: State<'_, ConversionController>,
+) -> AppResult<()> {
+    if cfg!(not(unix)) {
+        return Err(AppError::new(
+            AppErrorCode::Io,
+            "Pausing is not supported on this platform",
+        ));
+    }
+    if state.set_paused(true) {
+        emit_batch_status(&app, ConversionStatus::Paused)?;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn resume_conversion(
+    app: AppHandle,
+    state: State<'_, ConversionController>,
+) -> AppResult<()> {
+    if state.set_paused(false) {
+        emit_batch_status(&app, ConversionStatus::Processing)?;
+    }
     Ok(())
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_overrides() {
+        let mut params: VideoConversionParams = serde_json::from_str(
+            r#"{"input_folder":"/in","output_folder":"","target_fps":30,"cpu_limit":75,
+                "keep_audio":true,"audio_bitrate":192,"use_custom_video_quality":false,
+                "video_quality":23,"files":["/in/a.mp4","/in/b.mp4"],
+                "overrides":{"/in/b.mp4":{"target_fps":25,"keep_audio":false}}}"#,
+        )
+        .unwrap();
+        assert!(validate_conversion_params(&params).is_ok());
+
+        let a = params.file_settings("/in/a.mp4");
+        assert_eq!((a.target_fps, a.keep_audio), (30.0, true));
+        let b = params.file_settings("/in/b.mp4");
+        assert_eq!((b.target_fps, b.keep_audio, b.audio_bitrate), (25.0, false, 192));
+
+        params.overrides.get_mut("/in/b.mp4").unwrap().target_fps = Some(0.0);
+        let err = validate_conversion_params(&params).unwrap_err();
+        assert!(matches!(err.code, AppErrorCode::InvalidFps));
+        assert!(err.details.unwrap().starts_with("/in/b.mp4: "));
+    }
+
+    #[test]
+    fn test_overlapping_roots_are_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let (a, b) = (dir.path().join("a"), dir.path().join("b"));
+        std::fs::create_dir_all(a.join("day1")).unwrap();
+        std::fs::create_dir_all(&b).unwrap();
+        let params_for = |roots: &[&Path]| {
+            let roots: Vec<String> = roots.iter().map(|p| p.to_string_lossy().into()).collect();
+            serde_json::from_value::<VideoConversionParams>(serde_json::json!({
+                "input_folder": "", "output_folder": "", "target_fps": 30, "cpu_limit": 75,
+                "keep_audio": true, "audio_bitrate": 192, "use_custom_video_quality": false,
+                "video_quality": 23, "files": [], "input_roots": roots
+            }))
+            .unwrap()
+        };
+
+        assert_eq!(resolve_input_roots(&params_for(&[&a, &b])).unwrap().len(), 2);
+        for roots in [[&a, &a.join("day1")], [&a.join("day1"), &a], [&b, &b]] {
+            let result = resolve_input_roots(&params_for(&[roots[0], roots[1]]));
+            let err = result.map(|_| ()).unwrap_err();
+            assert!(matches!(err.code, AppErrorCode::InvalidInputPath));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_extensionless_input_gets_container_extension() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("clip");
+        std::fs::write(&input, b"\x1A\x45\xDF\xA3\x01\0").unwrap();
+        let root = InputRoot {
+            path: dir.path().to_string_lossy().to_string(),
+            canonical: dir.path().to_path_buf(),
+            output_dir: dir.path().join("out"),
+        };
+        let video_file = VideoFile {
+            path: input.to_string_lossy().to_string(),
+            name: "clip".into(),
+            relative_dir: String::new(),
+            size: 6,
+            thumbnail: None,
+            provenance: None,
+            clip: None,
+            duplicate_of: None,
+            container: sniff_misnamed(&input).await,
+        };
+        assert_eq!(video_file.container.as_deref(), Some("matroska"));
+        assert_eq!(
+            derive_output_path(&root, &video_file, 24.0),
+            dir.path().join("out").join("clip_24fps.mkv")
+        );
+    }
+
+    #[test]
+    fn test_resolve_output() {
+        let dir = tempfile::tempdir().unwrap();
+        let base = dir.path().join("a_24fps.mp4");
+        let mut claimed = HashSet::new();
+        assert_eq!(
+            resolve_output(&base, ConflictPolicy::Skip, &mut claimed),
+            (Some(base.clone()), OutputAction::Created)
+        );
+
+        std::fs::write(&base, b"old").unwrap();
+        std::fs::write(dir.path().join("a_24fps_1.mp4"), b"old").unwrap();
+        let mut claimed = HashSet::new();
+        assert_eq!(
+            resolve_output(&base, ConflictPolicy::Overwrite, &mut claimed),
+            (Some(base.clone()), OutputAction::Overwritten)
+        );
+        assert_eq!(
+            resolve_output(&base, ConflictPolicy::Skip, &mut claimed),
+            (None, OutputAction::Kept)
+        );
+        // "_2" is claimed by the first rename of this batch
+        let renamed = |n| Some(dir.path().join(format!("a_24fps_{}.mp4", n)));
+        assert_eq!(
+            resolve_output(&base, ConflictPolicy::Rename, &mut claimed),
+            (renamed(2), OutputAction::Renamed)
+        );
+        assert_eq!(
+            resolve_output(&base, ConflictPolicy::Rename, &mut claimed),
+            (renamed(3), OutputAction::Renamed)
+        );
+
+        // Two jobs of one batch mapping to the same output
+        let mut claimed = HashSet::new();
+        assert_eq!(
+            resolve_output(&base, ConflictPolicy::Overwrite, &mut claimed),
+            (Some(base.clone()), OutputAction::Overwritten)
+        );
+        assert_eq!(
+            resolve_output(&base, ConflictPolicy::Overwrite, &mut claimed),
+            (renamed(2), OutputAction::Renamed)
+        );
+    }
+}
@@ -0,0 +1,421 @@
+// Free FPS - Video Frame Rate Converter
+// Copyright (C) 2025 undelalune
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+// Watch-folder mode: new clips dropped into the input folders are converted
+// automatically with the parameters given when the watch was started.
+
+use crate::commands::scan::list_video_files;
+use crate::commands::video::{
+    resolve_input_roots, run_conversion, validate_conversion_params, ConversionController,
+    RunMode, VideoConversionParams,
+};
+use crate::errors::{AppError, AppErrorCode, AppResult};
+use crate::utils::bundled_ffmpeg::{get_ffmpeg_tools, FfmpegTools};
+use crate::utils::logger::log_error;
+use crate::utils::partial_output::is_partial_path;
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio::fs;
+use tokio::sync::{mpsc, Mutex};
+use tokio_util::sync::CancellationToken;
+
+/// A file must keep its size and modification time this long before it is queued
+const STABLE_FOR: Duration = Duration::from_secs(5);
+const TICK: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum WatchFileStatus {
+    Queued,
+    Started,
+    Finished,
+    Failed,
+}
+
+/// Payload of the `watch-file` event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WatchFileEvent {
+    pub path: String,
+    pub name: String,
+    pub status: WatchFileStatus,
+    pub error: Option<AppError>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WatchStatus {
+    pub active: bool,
+    /// Watched input folders
+    pub folders: Vec<String>,
+    /// Files waiting for their turn
+    pub queued: usize,
+    /// File being converted
+    pub current: Option<String>,
+    pub finished: usize,
+    pub failed: usize,
+}
+
+struct WatchSession {
+    cancel: CancellationToken,
+    // Dropping the watcher stops the notifications
+    _watcher: RecommendedWatcher,
+    status: Arc<Mutex<WatchStatus>>,
+}
+
+/// State shared by the two tasks of a watch session
+#[derive(Clone)]
+struct WatchTask {
+    app: AppHandle,
+    params: VideoConversionParams,
+    status: Arc<Mutex<WatchStatus>>,
+    cancel: CancellationToken,
+}
+
+#[derive(Default)]
+pub struct WatchController {
+    session: Mutex<Option<WatchSession>>,
+}
+
+fn emit_file_event(app: &AppHandle, path: &str, status: WatchFileStatus, error: Option<AppError>) {
+    let name = Path::new(path)
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string();
+    let _ = app.emit(
+        "watch-file",
+        &WatchFileEvent {
+            path: path.to_string(),
+            name,
+            status,
+            error,
+        },
+    );
+}
+
+/// True when a notification is about something else than our own outputs:
+/// files in the output folders and partial files don't trigger a rescan.
+/// Events without paths (e.g. "rescan needed") always count.
+fn is_relevant_event(event: &notify::Event, ignored_dirs: &[PathBuf]) -> bool {
+    event.paths.is_empty()
+        || event.paths.iter().any(|p| {
+            !is_partial_path(p) && !ignored_dirs.iter().any(|dir| p.starts_with(dir))
+        })
+}
+
+/// Start watching the input folders (`input_roots`, or `input_folder`).
+/// Files already in the folders are left alone; new ones are converted once
+/// they have stopped growing. A running watch is replaced.
+#[tauri::command]
+pub async fn start_watch(
+    app: AppHandle,
+    params: VideoConversionParams,
+    state: State<'_, WatchController>,
+) -> AppResult<()> {
+    validate_conversion_params(&params)?;
+    let roots = resolve_input_roots(&params)?;
+    let tools = get_ffmpeg_tools(&app)?;
+
+    let mut guard = state.session.lock().await;
+    if let Some(old) = guard.take() {
+        old.cancel.cancel();
+    }
+
+    // Never pick up our own outputs when an output folder is inside an input folder
+    let mut exclude: Vec<PathBuf> = Vec::new();
+    for root in &roots {
+        fs::create_dir_all(&root.output_dir).await?;
+        exclude.extend(root.output_dir.canonicalize());
+    }
+    let folders: Vec<String> = roots.iter().map(|r| r.path.clone()).collect();
+
+    let cancel = CancellationToken::new();
+    let mut known: HashSet<String> = HashSet::new();
+    for folder in &folders {
+        let initial = list_video_files(
+            folder.clone(),
+            &params.scan,
+            &exclude,
+            Some(&tools),
+            cancel.clone(),
+        )
+        .await?;
+        known.extend(initial.files.into_iter().map(|f| f.path));
+    }
+
+    // Event paths are spelled like the watched folders, which need not be canonical
+    let ignored_dirs: Vec<PathBuf> = roots
+        .iter()
+        .map(|r| r.output_dir.clone())
+        .chain(exclude.iter().cloned())
+        .collect();
+    let (event_tx, event_rx) = mpsc::unbounded_channel::<()>();
+    let mut watcher = notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+        if res.is_ok_and(|event| is_relevant_event(&event, &ignored_dirs)) {
+            let _ = event_tx.send(());
+        }
+    })
+    .map_err(|e| AppError::new(AppErrorCode::Io, e.to_string()))?;
+    let mode = if params.scan.recursive {
+        RecursiveMode::Recursive
+    } else {
+        RecursiveMode::NonRecursive
+    };
+    for folder in &folders {
+        watcher
+            .watch(Path::new(folder), mode)
+            .map_err(|e| AppError::new(AppErrorCode::Io, e.to_string()))?;
+    }
+
+    let status = Arc::new(Mutex::new(WatchStatus {
+        active: true,
+        folders: folders.clone(),
+        ..WatchStatus::default()
+    }));
+    let (queue_tx, queue_rx) = mpsc::unbounded_channel::<String>();
+
+    let task = WatchTask {
+        app,
+        params,
+        status: status.clone(),
+        cancel: cancel.clone(),
+    };
+    tauri::async_runtime::spawn(detect_new_files(
+        task.clone(),
+        tools,
+        folders,
+        exclude,
+        known,
+        event_rx,
+        queue_tx,
+    ));
+    tauri::async_runtime::spawn(convert_queued_files(task, queue_rx));
+
+    *guard = Some(WatchSession {
+        cancel,
+        _watcher: watcher,
+        status,
+    });
+    Ok(())
+}
+
+/// Stop watching. A conversion in progress is cancelled.
+#[tauri::command]
+pub async fn stop_watch(state: State<'_, WatchController>) -> AppResult<()> {
+    if let Some(session) = state.session.lock().await.take() {
+        session.cancel.cancel();
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_watch_status(state: State<'_, WatchController>) -> AppResult<WatchStatus> {
+    match state.session.lock().await.as_ref() {
+        Some(session) => Ok(session.status.lock().await.clone()),
+        None => Ok(WatchStatus::default()),
+    }
+}
+
+/// Rescan on filesystem notifications (through the regular scan, so extensions
+/// and filters apply) and queue new files once their size and modification
+/// time have stayed unchanged for [`STABLE_FOR`].
+async fn detect_new_files(
+    task: WatchTask,
+    tools: FfmpegTools,
+    folders: Vec<String>,
+    exclude: Vec<PathBuf>,
+    mut known: HashSet<String>,
+    mut events: mpsc::UnboundedReceiver<()>,
+    queue: mpsc::UnboundedSender<String>,
+) {
+    let WatchTask {
+        app,
+        params,
+        status,
+        cancel,
+    } = task;
+    let mut pending: HashMap<String, (u64, Option<SystemTime>, Instant)> = HashMap::new();
+    let mut dirty = false;
+    let mut tick = tokio::time::interval(TICK);
+
+    loop {
+        tokio::select! {
+            _ = cancel.cancelled() => break,
+            Some(()) = events.recv() => {
+                dirty = true;
+                continue;
+            }
+            _ = tick.tick() => {}
+        }
+
+        if dirty {
+            dirty = false;
+            let mut listed = Vec::new();
+            let mut failed = None;
+            for folder in &folders {
+                match list_video_files(
+                    folder.clone(),
+                    &params.scan,
+                    &exclude,
+                    Some(&tools),
+                    cancel.clone(),
+                )
+                .await
+                {
+                    Ok(result) => listed.extend(result.files),
+                    Err(e) => {
+                        failed = Some(e);
+                        break;
+                    }
+                }
+            }
+            match failed {
+                None => {
+                    // Deleted files may come back under the same name
+                    known.retain(|p| Path::new(p).exists());
+                    let candidates: HashSet<String> = listed
+                        .into_iter()
+                        .map(|f| f.path)
+                        .filter(|p| !known.contains(p))
+                        .collect();
+                    pending.retain(|p, _| candidates.contains(p));
+                    for path in candidates {
+                        pending
+                            .entry(path)
+                            .or_insert((u64::MAX, None, Instant::now()));
+                    }
+                }
+                Some(_) if cancel.is_cancelled() => break,
+                Some(e) => {
+                    log_error("WatchScanFailed", &format!("{:?}", e)).await;
+                }
+            }
+        }
+
+        let mut ready = Vec::new();
+        for (path, (size, mtime, since)) in pending.iter_mut() {
+            let Ok(meta) = fs::metadata(path.as_str()).await else {
+                continue;
+            };
+            let current = (meta.len(), meta.modified().ok());
+            if current != (*size, *mtime) {
+                (*size, *mtime) = current;
+                *since = Instant::now();
+            } else if since.elapsed() >= STABLE_FOR {
+                ready.push(path.clone());
+            }
+        }
+
+        for path in ready {
+            pending.remove(&path);
+            known.insert(path.clone());
+            status.lock().await.queued += 1;
+            emit_file_event(&app, &path, WatchFileStatus::Queued, None);
+            if queue.send(path).is_err() {
+                return;
+            }
+        }
+    }
+}
+
+/// Convert queued files one at a time through the same path as `convert_videos`.
+async fn convert_queued_files(task: WatchTask, mut queue: mpsc::UnboundedReceiver<String>) {
+    let WatchTask {
+        app,
+        params,
+        status,
+        cancel,
+    } = task;
+    loop {
+        let path = tokio::select! {
+            _ = cancel.cancelled() => break,
+            next = queue.recv() => match next {
+                Some(p) => p,
+                None => break,
+            },
+        };
+
+        // Wait for manual batches to finish; conversions never run in parallel
+        let controller = app.state::<ConversionController>();
+        let _permit = tokio::select! {
+            _ = cancel.cancelled() => break,
+            permit = controller.conversion_limiter().acquire() => permit,
+        };
+
+        {
+            let mut s = status.lock().await;
+            s.queued = s.queued.saturating_sub(1);
+            s.current = Some(path.clone());
+        }
+        emit_file_event(&app, &path, WatchFileStatus::Started, None);
+
+        let file_params = VideoConversionParams {
+            files: vec![path.clone()],
+            ..params.clone()
+        };
+        // Stopped by `cancel_conversion` like a manual batch, and by `stop_watch`
+        let file_cancel = controller.child_token().await;
+        let stop_watch = async {
+            cancel.cancelled().await;
+            file_cancel.cancel();
+            std::future::pending().await
+        };
+        let result = tokio::select! {
+            result = run_conversion(&app, &file_params, file_cancel.clone(), RunMode::Watch) => {
+                result
+            }
+            result = stop_watch => result,
+        };
+
+        let error = match result {
+            Ok(summary) if summary.failed == 0 => None,
+            Ok(_) => Some(AppError::code_only(AppErrorCode::FfmpegFailed)),
+            Err(e) => Some(e),
+        };
+        {
+            let mut s = status.lock().await;
+            s.current = None;
+            if error.is_none() {
+                s.finished += 1;
+            } else {
+                s.failed += 1;
+            }
+        }
+        match error {
+            None => emit_file_event(&app, &path, WatchFileStatus::Finished, None),
+            Some(e) => emit_file_event(&app, &path, WatchFileStatus::Failed, Some(e)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use notify::{Event, EventKind};
+
+    #[test]
+    fn test_own_outputs_are_not_relevant() {
+        let ignored = [PathBuf::from("/in/converted_videos_24fps")];
+        let event = |p: &str| Event::new(EventKind::Any).add_path(PathBuf::from(p));
+        assert!(is_relevant_event(&event("/in/new.mp4"), &ignored));
+        assert!(!is_relevant_event(&event("/in/new_24fps.partial.mp4"), &ignored));
+        assert!(!is_relevant_event(&event("/in/converted_videos_24fps/a_24fps.mp4"), &ignored));
+        assert!(is_relevant_event(&Event::new(EventKind::Any), &ignored));
+    }
+}
@@ -44,6 +44,8 @@ pub enum AppErrorCode {
     PathTraversalDetected = 26,
     InvalidInputPath = 27,
     LicenseNotFound = 28,
+    InvalidMetadataTag = 29,
+    InvalidScanFilter = 30,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -19,18 +19,33 @@ mod errors;
 mod utils;
 
 use commands::license::open_bundled_license;
+use commands::scan::{cancel_scan, scan_video_files};
+use commands::summary::get_folder_summary;
 use commands::video::{
-    cancel_conversion, convert_videos, get_video_files, get_video_thumbnail, ConversionController,
+    cancel_conversion, cancel_file_conversion, clear_probe_cache, convert_videos,
+    discard_interrupted_job, get_interrupted_job, get_video_files, get_video_probe,
+    get_video_thumbnail, pause_conversion, read_conversion_provenance, resume_conversion,
+    resume_interrupted_job, ConversionController,
 };
+use commands::watch::{get_watch_status, start_watch, stop_watch, WatchController};
 use utils::gpu::get_gpu_info;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
         .manage(ConversionController::default())
+        .manage(WatchController::default())
         .setup(|app| {
             // Initialize the log file path next to `settings.json`
             crate::utils::logger::init_log_path(&app.handle());
+            // Probe cache lives in the same app data dir
+            crate::utils::probe_cache::init_cache_path(&app.handle());
+            // So is the journal of the running batch
+            crate::utils::job_queue::init_job_path(&app.handle());
+            // Remove partial outputs left by a crash
+            crate::utils::partial_output::init_partial_list(&app.handle());
+            // Keep an interrupted batch ready for the UI to offer resuming
+            tauri::async_runtime::block_on(crate::utils::job_queue::recover_interrupted());
             Ok(())
         })
         .plugin(tauri_plugin_os::init())
@@ -41,12 +56,33 @@ pub fn run() {
         .plugin(tauri_plugin_shell::init())
         .invoke_handler(tauri::generate_handler![
             get_video_files,
+            scan_video_files,
+            cancel_scan,
+            get_folder_summary,
             get_video_thumbnail,
+            get_video_probe,
+            clear_probe_cache,
+            read_conversion_provenance,
             convert_videos,
             cancel_conversion,
+            cancel_file_conversion,
+            pause_conversion,
+            resume_conversion,
+            get_interrupted_job,
+            resume_interrupted_job,
+            discard_interrupted_job,
+            start_watch,
+            stop_watch,
+            get_watch_status,
             open_bundled_license,
             get_gpu_info,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|_app, event| {
+            if let tauri::RunEvent::Exit = event {
+                // Probe results still waiting for the debounced write
+                tauri::async_runtime::block_on(crate::utils::probe_cache::flush_probe_cache());
+            }
+        });
 }
\ No newline at end of file
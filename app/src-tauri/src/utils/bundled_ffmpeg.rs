@@ -119,3 +119,19 @@ pub fn get_ffprobe_path(app: &tauri::AppHandle) -> AppResult<PathBuf> {
         "Bundled ffprobe not found in sidecar or resources".to_string(),
     ))
 }
+
+/// Bundled tool paths as strings, ready to pass to `Command`.
+/// ffprobe is optional: probing falls back to parsing the ffmpeg banner.
+#[derive(Debug, Clone)]
+pub struct FfmpegTools {
+    pub ffmpeg: String,
+    pub ffprobe: Option<String>,
+}
+
+pub fn get_ffmpeg_tools(app: &tauri::AppHandle) -> AppResult<FfmpegTools> {
+    let ffmpeg = get_ffmpeg_path(app)?.to_string_lossy().to_string();
+    let ffprobe = get_ffprobe_path(app)
+        .ok()
+        .map(|p| p.to_string_lossy().to_string());
+    Ok(FfmpegTools { ffmpeg, ffprobe })
+}
@@ -0,0 +1,271 @@
+// Free FPS - Video Frame Rate Converter
+// Copyright (C) 2025 undelalune
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+// Camera memory card layouts. Cameras bury their clips a few folders below
+// the card root and name them by counter (00000.MTS, C0001.MP4), so clips
+// are located through the known layouts and renamed after their recording time.
+
+use chrono::{DateTime, FixedOffset, Local, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tokio::fs;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum CardLayout {
+    /// PRIVATE/AVCHD/BDMV/STREAM/*.MTS
+    Avchd,
+    /// PRIVATE/M4ROOT/CLIP/*.MP4 (XAVC S/HS) or XDROOT/Clip/*.MXF, with XML sidecars
+    Xavc,
+    /// DCIM/100XXXXX/* (GoPro, DJI, phones, compacts)
+    Dcim,
+}
+
+/// Clip folders of each layout, relative to the card root
+const CLIP_DIRS: [(&[&str], CardLayout); 4] = [
+    (&["PRIVATE", "AVCHD", "BDMV", "STREAM"], CardLayout::Avchd),
+    (&["AVCHD", "BDMV", "STREAM"], CardLayout::Avchd),
+    (&["PRIVATE", "M4ROOT", "CLIP"], CardLayout::Xavc),
+    (&["XDROOT", "Clip"], CardLayout::Xavc),
+];
+
+/// Per-clip information for files found in a card layout.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CameraClip {
+    pub layout: CardLayout,
+    /// "<recording time>_<original name>", used for display and output names
+    pub display_name: String,
+    /// RFC 3339 with the camera's UTC offset when known
+    pub recorded_at: Option<String>,
+    /// "Sony ILCE-7SM3", from the sidecar
+    pub device: Option<String>,
+    /// Recording time from the sidecar, normalized to UTC. Written as the
+    /// output's creation_time instead of the container tag, which some
+    /// cameras fill with local time marked as UTC.
+    pub creation_time: Option<String>,
+}
+
+/// Fields read from a Sony XML sidecar
+#[derive(Debug, Default)]
+pub struct SidecarMeta {
+    pub recorded: Option<DateTime<FixedOffset>>,
+    pub device: Option<String>,
+}
+
+/// Child folder matched case-insensitively; cards are FAT/exFAT and
+/// mounts differ in how they present names.
+async fn child_dir(dir: &Path, name: &str) -> Option<PathBuf> {
+    let exact = dir.join(name);
+    if fs::metadata(&exact).await.is_ok_and(|m| m.is_dir()) {
+        return Some(exact);
+    }
+    let mut entries = fs::read_dir(dir).await.ok()?;
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        if entry
+            .file_name()
+            .to_string_lossy()
+            .eq_ignore_ascii_case(name)
+            && entry.file_type().await.is_ok_and(|t| t.is_dir())
+        {
+            return Some(entry.path());
+        }
+    }
+    None
+}
+
+/// Clip folders of a card mounted at `root`, empty when `root` is not a card.
+pub async fn card_clip_dirs(root: &Path) -> Vec<(CardLayout, PathBuf)> {
+    let mut found = Vec::new();
+    'layouts: for (parts, layout) in CLIP_DIRS {
+        let mut dir = root.to_path_buf();
+        for part in parts {
+            match child_dir(&dir, part).await {
+                Some(d) => dir = d,
+                None => continue 'layouts,
+            }
+        }
+        found.push((layout, dir));
+    }
+
+    // DCIM holds one folder per 999 files: 100GOPRO, 101MEDIA, ...
+    if let Some(dcim) = child_dir(root, "DCIM").await {
+        if let Ok(mut entries) = fs::read_dir(&dcim).await {
+            let mut dirs = Vec::new();
+            while let Ok(Some(entry)) = entries.next_entry().await {
+                let name = entry.file_name().to_string_lossy().to_string();
+                if name.len() > 3
+                    && name.as_bytes()[..3].iter().all(u8::is_ascii_digit)
+                    && entry.file_type().await.is_ok_and(|t| t.is_dir())
+                {
+                    dirs.push(entry.path());
+                }
+            }
+            dirs.sort();
+            found.extend(dirs.into_iter().map(|d| (CardLayout::Dcim, d)));
+        }
+    }
+    found
+}
+
+/// Layout a clip belongs to, judged from the folders above it.
+pub fn layout_of(path: &Path) -> Option<CardLayout> {
+    let mut parents = path.ancestors().skip(1).map(|p| {
+        p.file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_uppercase()
+    });
+    let parent = parents.next()?;
+    let grandparent = parents.next().unwrap_or_default();
+    match (grandparent.as_str(), parent.as_str()) {
+        ("BDMV", "STREAM") => Some(CardLayout::Avchd),
+        ("M4ROOT" | "XDROOT", "CLIP") => Some(CardLayout::Xavc),
+        ("DCIM", p) if p.len() > 3 && p.as_bytes()[..3].iter().all(u8::is_ascii_digit) => {
+            Some(CardLayout::Dcim)
+        }
+        _ => None,
+    }
+}
+
+/// Recording time and device from a Sony NonRealTimeMeta sidecar.
+pub fn parse_sony_xml(xml: &str) -> Option<SidecarMeta> {
+    let doc = roxmltree::Document::parse(xml).ok()?;
+    let root = doc.root_element();
+    if root.tag_name().name() != "NonRealTimeMeta" {
+        return None;
+    }
+    let child = |name: &str| root.children().find(|n| n.tag_name().name() == name);
+    let recorded = child("CreationDate")
+        .and_then(|n| n.attribute("value"))
+        .and_then(|v| DateTime::parse_from_rfc3339(v).ok());
+    let device = child("Device").and_then(|n| {
+        let parts: Vec<&str> = [n.attribute("manufacturer"), n.attribute("modelName")]
+            .into_iter()
+            .flatten()
+            .filter(|s| !s.is_empty())
+            .collect();
+        (!parts.is_empty()).then(|| parts.join(" "))
+    });
+    Some(SidecarMeta { recorded, device })
+}
+
+/// Sony writes "C0001M01.XML" next to "C0001.MP4".
+async fn read_sidecar(path: &Path) -> Option<String> {
+    let stem = path.file_stem()?.to_string_lossy();
+    let dir = path.parent()?;
+    for ext in ["XML", "xml"] {
+        if let Ok(text) = fs::read_to_string(dir.join(format!("{}M01.{}", stem, ext))).await {
+            return Some(text);
+        }
+    }
+    None
+}
+
+/// Clip information for a file inside a camera card layout, `None` for
+/// other files. Without a sidecar the recording time is the file's
+/// modification time, which cameras set when they close the clip.
+pub async fn read_camera_clip(path: &Path) -> Option<CameraClip> {
+    let layout = layout_of(path)?;
+    let name = path.file_name()?.to_string_lossy().to_string();
+
+    let SidecarMeta { recorded, device } = match layout {
+        CardLayout::Xavc => read_sidecar(path)
+            .await
+            .and_then(|xml| parse_sony_xml(&xml))
+            .unwrap_or_default(),
+        _ => SidecarMeta::default(),
+    };
+    let creation_time = recorded.map(|t| t.with_timezone(&Utc).to_rfc3339());
+    let recorded = match recorded {
+        Some(t) => Some(t),
+        None => fs::metadata(path)
+            .await
+            .ok()
+            .and_then(|m| m.modified().ok())
+            .map(|t| DateTime::<Local>::from(t).fixed_offset()),
+    };
+
+    let display_name = match recorded {
+        Some(t) => format!("{}_{}", t.format("%Y-%m-%d_%H-%M-%S"), name),
+        None => name,
+    };
+    Some(CameraClip {
+        layout,
+        display_name,
+        recorded_at: recorded.map(|t| t.to_rfc3339()),
+        device,
+        creation_time,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layout_of() {
+        assert_eq!(
+            layout_of(Path::new("/card/PRIVATE/AVCHD/BDMV/STREAM/00000.MTS")),
+            Some(CardLayout::Avchd)
+        );
+        assert_eq!(
+            layout_of(Path::new("/card/PRIVATE/M4ROOT/CLIP/C0001.MP4")),
+            Some(CardLayout::Xavc)
+        );
+        assert_eq!(
+            layout_of(Path::new("/card/DCIM/100GOPRO/GX010001.MP4")),
+            Some(CardLayout::Dcim)
+        );
+        assert_eq!(layout_of(Path::new("/videos/DCIM/clip.mp4")), None);
+        assert_eq!(layout_of(Path::new("/videos/clip.mp4")), None);
+    }
+
+    #[test]
+    fn test_parse_sony_xml() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<NonRealTimeMeta xmlns="urn:schemas-professionalDisc:nonRealTimeMeta:ver.2.00" lastUpdate="2023-05-01T12:35:40+09:00">
+  <Duration value="2400"/>
+  <CreationDate value="2023-05-01T12:34:56+09:00"/>
+  <Device manufacturer="Sony" modelName="ILCE-7SM3" serialNo="1234567"/>
+</NonRealTimeMeta>"#;
+        let meta = parse_sony_xml(xml).unwrap();
+        let recorded = meta.recorded.unwrap();
+        assert_eq!(recorded.to_rfc3339(), "2023-05-01T12:34:56+09:00");
+        assert_eq!(
+            recorded.with_timezone(&Utc).to_rfc3339(),
+            "2023-05-01T03:34:56+00:00"
+        );
+        assert_eq!(meta.device.as_deref(), Some("Sony ILCE-7SM3"));
+
+        assert!(parse_sony_xml("<Other/>").is_none());
+        assert!(parse_sony_xml("not xml").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_card_clip_dirs() {
+        let card = tempfile::tempdir().unwrap();
+        let stream = card.path().join("PRIVATE/AVCHD/BDMV/STREAM");
+        std::fs::create_dir_all(&stream).unwrap();
+        std::fs::create_dir_all(card.path().join("DCIM/100GOPRO")).unwrap();
+        std::fs::create_dir_all(card.path().join("DCIM/MISC")).unwrap();
+
+        let dirs = card_clip_dirs(card.path()).await;
+        assert_eq!(dirs.len(), 2);
+        assert_eq!(dirs[0], (CardLayout::Avchd, stream));
+        assert_eq!(dirs[1].0, CardLayout::Dcim);
+
+        assert!(card_clip_dirs(&card.path().join("DCIM")).await.is_empty());
+    }
+}
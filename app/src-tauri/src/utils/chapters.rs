@@ -0,0 +1,240 @@
+// Free FPS - Video Frame Rate Converter
+// Copyright (C) 2025 undelalune
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+// Chaptered recordings: cameras split long takes into several files, which
+// are joined back into one conversion with the concat demuxer.
+
+use crate::utils::ffmpeg::VideoProbe;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::OnceLock;
+use std::time::SystemTime;
+
+/// Largest gap between the end of a file and the start of the next one in
+/// a numbered sequence, in seconds
+const MAX_GAP_SECS: f64 = 3.0;
+
+/// Files of one recording, in playback order.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChapterGroup {
+    pub files: Vec<String>,
+    /// Name of the first chapter; the joined output is named after it
+    pub name: String,
+    /// Combined duration in seconds, when every chapter was probed
+    pub duration: Option<f64>,
+}
+
+/// A scanned file considered for chapter detection
+#[derive(Clone, Copy)]
+pub struct ChapterCandidate<'a> {
+    pub path: &'a str,
+    pub modified: Option<SystemTime>,
+    pub probe: Option<&'a VideoProbe>,
+}
+
+/// How a file name places it in a sequence
+#[derive(Debug, PartialEq)]
+enum SeriesKey {
+    /// GoPro names carry the chapter: GX010123, GX020123 (HERO5+),
+    /// GOPR0123, GP010123 (older models)
+    GoPro { series: String, chapter: u32 },
+    /// Counter-named files (DJI_0001, FILE0002F); only joined when their
+    /// timing shows one continuous recording
+    Numbered { series: String, number: u64 },
+}
+
+fn series_key(path: &Path) -> Option<SeriesKey> {
+    static GOPRO: OnceLock<Regex> = OnceLock::new();
+    static GOPRO_OLD: OnceLock<Regex> = OnceLock::new();
+    static DIGITS: OnceLock<Regex> = OnceLock::new();
+
+    let name = path.file_name()?.to_str()?.to_uppercase();
+    let gopro = GOPRO.get_or_init(|| Regex::new(r"^(G[HX])(\d{2})(\d{4})\.(\w+)$").unwrap());
+    if let Some(c) = gopro.captures(&name) {
+        return Some(SeriesKey::GoPro {
+            series: format!("{}{}.{}", &c[1], &c[3], &c[4]),
+            chapter: c[2].parse().ok()?,
+        });
+    }
+    let old = GOPRO_OLD.get_or_init(|| Regex::new(r"^GO?P(R|\d{2})(\d{4})\.(\w+)$").unwrap());
+    if let Some(c) = old.captures(&name) {
+        // GOPR0123 is the first chapter, GP010123 the second
+        let chapter = if &c[1] == "R" { 0 } else { c[1].parse().ok()? };
+        return Some(SeriesKey::GoPro {
+            series: format!("GP{}.{}", &c[2], &c[3]),
+            chapter,
+        });
+    }
+
+    // The last number is the counter; other numbers (dates in DJI names) may differ
+    let (stem, ext) = name.rsplit_once('.').unwrap_or((&name, ""));
+    let digits = DIGITS.get_or_init(|| Regex::new(r"\d+").unwrap());
+    let last = digits.find_iter(stem).last()?;
+    let series = format!(
+        "{}#{}.{}",
+        digits.replace_all(&stem[..last.start()], "#"),
+        &stem[last.end()..],
+        ext
+    );
+    Some(SeriesKey::Numbered {
+        series,
+        number: last.as_str().parse().ok()?,
+    })
+}
+
+/// Same stream layout, so the concat demuxer can join them without re-timing
+fn compatible(a: Option<&VideoProbe>, b: Option<&VideoProbe>) -> bool {
+    match (a, b) {
+        (Some(a), Some(b)) => {
+            a.video_codec == b.video_codec
+                && a.width == b.width
+                && a.height == b.height
+                && (a.fps - b.fps).abs() < 0.01
+        }
+        _ => true,
+    }
+}
+
+/// The next file starts where the previous one ended. Cameras set the
+/// modification time when they close a file, so a file starts at its
+/// modification time minus its duration.
+fn continuous(prev: &ChapterCandidate, next: &ChapterCandidate) -> bool {
+    let (Some(prev_end), Some(next_end), Some(probe)) = (prev.modified, next.modified, next.probe)
+    else {
+        return false;
+    };
+    let secs = |t: SystemTime| {
+        t.duration_since(SystemTime::UNIX_EPOCH)
+            .map(|d| d.as_secs_f64())
+            .unwrap_or(0.0)
+    };
+    let next_start = secs(next_end) - probe.duration_sec;
+    probe.duration_sec > 0.0 && (next_start - secs(prev_end)).abs() <= MAX_GAP_SECS
+}
+
+/// Chapter sequences among `files`, each as indices in playback order.
+/// Only files in the same folder are joined.
+pub fn find_chapter_sequences(files: &[ChapterCandidate]) -> Vec<Vec<usize>> {
+    // (folder, series) -> [(position, is GoPro, index)]
+    type Members = Vec<(u64, bool, usize)>;
+    let mut series: BTreeMap<(String, String), Members> = BTreeMap::new();
+    for (i, f) in files.iter().enumerate() {
+        let path = Path::new(f.path);
+        let dir = path
+            .parent()
+            .map(|p| p.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let (key, pos, gopro) = match series_key(path) {
+            Some(SeriesKey::GoPro { series, chapter }) => (series, chapter as u64, true),
+            Some(SeriesKey::Numbered { series, number }) => (series, number, false),
+            None => continue,
+        };
+        series.entry((dir, key)).or_default().push((pos, gopro, i));
+    }
+
+    let mut sequences = Vec::new();
+    for mut members in series.into_values() {
+        members.sort();
+        let mut run: Vec<usize> = Vec::new();
+        let mut prev_pos = 0;
+        for (pos, gopro, i) in members {
+            let joins = run.last().is_some_and(|&last| {
+                pos == prev_pos + 1
+                    && compatible(files[last].probe, files[i].probe)
+                    && (gopro || continuous(&files[last], &files[i]))
+            });
+            if !joins {
+                if run.len() > 1 {
+                    sequences.push(std::mem::take(&mut run));
+                }
+                run.clear();
+            }
+            run.push(i);
+            prev_pos = pos;
+        }
+        if run.len() > 1 {
+            sequences.push(run);
+        }
+    }
+    sequences
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::ffmpeg::test_probe;
+    use std::time::Duration;
+
+    fn probe(duration_sec: f64) -> VideoProbe {
+        test_probe(29.97, duration_sec, "h264")
+    }
+
+    #[test]
+    fn test_gopro_names() {
+        let files: Vec<ChapterCandidate> = [
+            "/c/GX020123.MP4",
+            "/c/GX010123.MP4",
+            "/c/GX010124.MP4",
+            "/c/GOPR0007.MP4",
+            "/c/GP010007.MP4",
+            "/other/GX030123.MP4",
+        ]
+        .iter()
+        .map(|p| ChapterCandidate {
+            path: p,
+            modified: None,
+            probe: None,
+        })
+        .collect();
+        let mut seqs = find_chapter_sequences(&files);
+        seqs.sort();
+        assert_eq!(seqs, vec![vec![1, 0], vec![3, 4]]);
+    }
+
+    #[test]
+    fn test_numbered_names_need_continuity() {
+        let t0 = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let (p1, p2, p3) = (probe(600.0), probe(600.0), probe(30.0));
+        let files = [
+            ChapterCandidate {
+                path: "/c/DJI_0001.MP4",
+                modified: Some(t0),
+                probe: Some(&p1),
+            },
+            ChapterCandidate {
+                path: "/c/DJI_0002.MP4",
+                modified: Some(t0 + Duration::from_secs(601)),
+                probe: Some(&p2),
+            },
+            // Recorded an hour later: a separate take
+            ChapterCandidate {
+                path: "/c/DJI_0003.MP4",
+                modified: Some(t0 + Duration::from_secs(4000)),
+                probe: Some(&p3),
+            },
+        ];
+        assert_eq!(find_chapter_sequences(&files), vec![vec![0, 1]]);
+
+        // Without probe data numbered files are never joined
+        let files: Vec<ChapterCandidate> = files
+            .iter()
+            .map(|f| ChapterCandidate { probe: None, ..*f })
+            .collect();
+        assert!(find_chapter_sequences(&files).is_empty());
+    }
+}
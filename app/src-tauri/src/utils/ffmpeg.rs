@@ -16,15 +16,25 @@
 
 use crate::errors::{AppError, AppErrorCode};
 use crate::utils::logger::{log_error, log_ffmpeg_command, rotate_log_if_needed};
+use crate::utils::hashing::quick_hash;
+use crate::utils::metadata_tags::{build_tag_args, MetadataTag, TemplateContext};
+use crate::utils::partial_output::PartialOutput;
+use crate::utils::probe_cache::probe_video_cached;
+use crate::utils::provenance::ConversionProvenance;
+use crate::utils::ffmpeg_banner::parse_ffmpeg_banner;
+use crate::utils::timecode::TimecodeInfo;
 use chrono::{DateTime, Utc};
-use regex::Regex;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
 use std::process::Stdio;
+use std::sync::atomic::{AtomicU64, Ordering};
 use tokio::{
     fs,
     io::AsyncBufReadExt,
-    process::Command,
-    time::{timeout, Duration},
+    process::{Child, Command},
+    sync::watch,
+    time::{Duration, Instant},
 };
 use tokio_util::sync::CancellationToken;
 
@@ -40,26 +50,188 @@ struct FfprobeJson {
 
 #[derive(Debug, Deserialize)]
 struct ProbeStream {
+    index: Option<u32>,
+    codec_type: Option<String>,
+    codec_name: Option<String>,
+    codec_tag_string: Option<String>,
+    width: Option<u32>,
+    height: Option<u32>,
     avg_frame_rate: Option<String>,
     r_frame_rate: Option<String>,
+    disposition: Option<ProbeDisposition>,
+    tags: Option<ProbeTags>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ProbeDisposition {
+    attached_pic: Option<u8>,
 }
 
 #[derive(Debug, Deserialize)]
 struct ProbeFormat {
     duration: Option<String>,
-    tags: Option<ProbeTags>,
+    tags: Option<HashMap<String, String>>,
 }
 
 #[derive(Debug, Deserialize)]
 struct ProbeTags {
-    creation_time: Option<String>,
+    timecode: Option<String>,
 }
 
-#[derive(Debug, Clone)]
+/// One stream of the probed file, as reported by ffprobe or the ffmpeg banner.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StreamInfo {
+    pub index: u32,
+    /// "video", "audio", "subtitle", "data" or "attachment"
+    pub codec_type: String,
+    pub codec_name: Option<String>,
+    /// Container codec tag, e.g. "avc1" or "tmcd"
+    pub codec_tag: Option<String>,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub fps: Option<f64>,
+    /// Cover art stored as a single-frame video stream
+    pub attached_pic: bool,
+    /// Raw `timecode` tag of the stream
+    pub timecode: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProbeError {
+    /// The input has no usable video stream (e.g. audio-only files, cover art only)
+    NoVideoStream,
+    Failed(String),
+}
+
+impl std::fmt::Display for ProbeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NoVideoStream => f.write_str("no video stream with a frame rate"),
+            Self::Failed(e) => f.write_str(e),
+        }
+    }
+}
+
+impl From<ProbeError> for String {
+    fn from(e: ProbeError) -> Self {
+        e.to_string()
+    }
+}
+
+impl ProbeError {
+    /// Prefix the message of a failure with the tool that reported it
+    fn context(self, tool: &str) -> Self {
+        match self {
+            Self::Failed(e) => Self::Failed(format!("{tool}: {e}")),
+            other => other,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VideoProbe {
     pub fps: f64,
+    /// Container duration in seconds; 0.0 when the container reports none
+    /// (`Duration: N/A`), see [`VideoProbe::duration`]
     pub duration_sec: f64,
     pub creation_time: Option<String>,
+    pub timecode: Option<TimecodeInfo>,
+    pub video_codec: Option<String>,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub has_audio: bool,
+    pub streams: Vec<StreamInfo>,
+    /// Set when the file is itself an output of Free FPS
+    pub provenance: Option<ConversionProvenance>,
+}
+
+impl VideoProbe {
+    /// Assemble the probe result from the parsed streams. The primary video stream
+    /// is the first one that is not cover art; its rate is the source fps.
+    pub(crate) fn from_streams(
+        streams: Vec<StreamInfo>,
+        duration_sec: f64,
+        format_tags: &HashMap<String, String>,
+    ) -> Result<Self, ProbeError> {
+        let primary = streams
+            .iter()
+            .find(|s| s.codec_type == "video" && !s.attached_pic && s.fps.is_some())
+            .ok_or(ProbeError::NoVideoStream)?;
+        let fps = primary.fps.unwrap_or_default();
+
+        let timecode =
+            Self::timecode_from_streams(&streams, primary, format_tags.get("timecode").cloned());
+
+        Ok(Self {
+            fps,
+            duration_sec,
+            creation_time: format_tags.get("creation_time").cloned(),
+            timecode,
+            video_codec: primary.codec_name.clone(),
+            width: primary.width,
+            height: primary.height,
+            has_audio: streams.iter().any(|s| s.codec_type == "audio"),
+            streams,
+            provenance: ConversionProvenance::from_tags(format_tags),
+        })
+    }
+
+    /// Container duration, `None` when unknown (raw elementary streams)
+    pub fn duration(&self) -> Option<f64> {
+        Some(self.duration_sec).filter(|d| *d > 0.0)
+    }
+
+    /// Start timecode lookup order: QuickTime `tmcd` track, video stream tag, container tag.
+    /// The `tmcd` track may carry its own rate; tags fall back to the video rate.
+    fn timecode_from_streams(
+        streams: &[StreamInfo],
+        primary: &StreamInfo,
+        format_timecode: Option<String>,
+    ) -> Option<TimecodeInfo> {
+        let video_fps = primary.fps.unwrap_or_default();
+
+        if let Some(tc) = streams
+            .iter()
+            .filter(|s| s.codec_tag.as_deref() == Some("tmcd"))
+            .find_map(|s| {
+                let rate = s.fps.unwrap_or(video_fps);
+                s.timecode
+                    .as_deref()
+                    .and_then(|v| TimecodeInfo::from_tag(v, rate))
+            })
+        {
+            return Some(tc);
+        }
+
+        primary
+            .timecode
+            .clone()
+            .or_else(|| {
+                streams
+                    .iter()
+                    .filter(|s| s.codec_type == "video")
+                    .find_map(|s| s.timecode.clone())
+            })
+            .or(format_timecode)
+            .and_then(|v| TimecodeInfo::from_tag(&v, video_fps))
+    }
+}
+
+/// 1920x1080 single-stream probe for tests.
+#[cfg(test)]
+pub(crate) fn test_probe(fps: f64, duration_sec: f64, codec: &str) -> VideoProbe {
+    let stream = StreamInfo {
+        index: 0,
+        codec_type: "video".into(),
+        codec_name: Some(codec.into()),
+        codec_tag: None,
+        width: Some(1920),
+        height: Some(1080),
+        fps: Some(fps),
+        attached_pic: false,
+        timecode: None,
+    };
+    VideoProbe::from_streams(vec![stream], duration_sec, &HashMap::new()).unwrap()
 }
 
 // ===== Utilities =====
@@ -98,107 +270,107 @@ fn apply_no_window(_: &mut Command) {}
 
 // ===== Probe via ffprobe/ffmpeg =====
 
-async fn probe_with_ffprobe(ffprobe_bin: &str, input: &str) -> Result<VideoProbe, String> {
+fn stream_fps(s: &ProbeStream) -> Option<f64> {
+    s.avg_frame_rate
+        .as_deref()
+        .and_then(parse_rational)
+        .filter(|f| *f > 0.0)
+        .or_else(|| s.r_frame_rate.as_deref().and_then(parse_rational))
+        .filter(|f| *f > 0.0)
+}
+
+impl From<ProbeStream> for StreamInfo {
+    fn from(s: ProbeStream) -> Self {
+        let fps = stream_fps(&s);
+        Self {
+            index: s.index.unwrap_or_default(),
+            codec_type: s.codec_type.unwrap_or_default(),
+            codec_name: s.codec_name,
+            codec_tag: s.codec_tag_string,
+            width: s.width,
+            height: s.height,
+            fps,
+            attached_pic: s
+                .disposition
+                .and_then(|d| d.attached_pic)
+                .is_some_and(|v| v != 0),
+            timecode: s.tags.and_then(|t| t.timecode),
+        }
+    }
+}
+
+async fn probe_with_ffprobe(ffprobe_bin: &str, input: &str) -> Result<VideoProbe, ProbeError> {
     let mut cmd = Command::new(ffprobe_bin);
     apply_no_window(&mut cmd);
 
     let output = cmd
-        .args(&[
+        .args([
             "-v",
             "quiet",
             "-print_format",
             "json",
             "-show_entries",
-            "stream=avg_frame_rate,r_frame_rate:format=duration:format_tags=creation_time",
-            "-select_streams",
-            "v:0",
+            "stream=index,codec_type,codec_name,codec_tag_string,width,height,avg_frame_rate,r_frame_rate:stream_disposition=attached_pic:stream_tags=timecode:format=duration:format_tags",
             "-i",
             input,
         ])
         .output()
         .await
-        .map_err(|e| format!("ffprobe spawn failed: {e}"))?;
+        .map_err(|e| ProbeError::Failed(format!("ffprobe spawn failed: {e}")))?;
 
     if !output.status.success() {
-        return Err("ffprobe failed".to_string());
+        return Err(ProbeError::Failed("ffprobe failed".to_string()));
     }
 
-    let json: FfprobeJson =
-        serde_json::from_slice(&output.stdout).map_err(|e| format!("ffprobe parse failed: {e}"))?;
-
-    let fps = json
-        .streams
-        .as_ref()
-        .and_then(|s| s.get(0))
-        .and_then(|s| s.avg_frame_rate.as_deref().or(s.r_frame_rate.as_deref()))
-        .and_then(parse_rational)
-        .ok_or_else(|| "ffprobe: FPS not found".to_string())?;
+    let json: FfprobeJson = serde_json::from_slice(&output.stdout)
+        .map_err(|e| ProbeError::Failed(format!("ffprobe parse failed: {e}")))?;
 
-    let duration_sec: f64 = json
+    let duration_sec = json
         .format
         .as_ref()
         .and_then(|f| f.duration.as_deref())
         .and_then(|s| s.parse::<f64>().ok())
-        .ok_or_else(|| "ffprobe: duration not found".to_string())?;
+        .unwrap_or(0.0);
 
-    let creation_time = json
-        .format
-        .and_then(|f| f.tags)
-        .and_then(|t| t.creation_time);
+    let format_tags = json.format.and_then(|f| f.tags).unwrap_or_default();
 
-    Ok(VideoProbe {
-        fps,
-        duration_sec,
-        creation_time,
-    })
+    let streams: Vec<StreamInfo> = json
+        .streams
+        .unwrap_or_default()
+        .into_iter()
+        .map(StreamInfo::from)
+        .collect();
+
+    VideoProbe::from_streams(streams, duration_sec, &format_tags).map_err(|e| e.context("ffprobe"))
 }
 
-async fn probe_with_ffmpeg(ffmpeg_bin: &str, input: &str) -> Result<VideoProbe, String> {
+async fn probe_with_ffmpeg(ffmpeg_bin: &str, input: &str) -> Result<VideoProbe, ProbeError> {
     let mut cmd = Command::new(ffmpeg_bin);
     apply_no_window(&mut cmd);
 
+    // Without an output ffmpeg exits with an error after printing the input banner
     let output = cmd
-        .args(&["-i", input, "-hide_banner"])
+        .args(["-hide_banner", "-i", input])
         .stderr(Stdio::piped())
         .stdout(Stdio::null())
         .output()
         .await
-        .map_err(|e| format!("ffmpeg probe spawn failed: {e}"))?;
+        .map_err(|e| ProbeError::Failed(format!("ffmpeg probe spawn failed: {e}")))?;
 
     let stderr = String::from_utf8_lossy(&output.stderr);
-
-    let fps_re = Regex::new(r"(?m)Stream.*Video.*?(\d+(?:\.\d+)?)\s*fps").unwrap();
-    let fps = fps_re
-        .captures(&stderr)
-        .and_then(|c| c.get(1))
-        .and_then(|m| m.as_str().parse::<f64>().ok())
-        .ok_or_else(|| "ffmpeg probe: FPS not found".to_string())?;
-
-    let dur_re = Regex::new(r"(?m)^\s*Duration:\s*(\d+):(\d+):(\d+(?:\.\d+)?)").unwrap();
-    let duration_sec = if let Some(c) = dur_re.captures(&stderr) {
-        let h: f64 = c.get(1).unwrap().as_str().parse().unwrap_or(0.0);
-        let m: f64 = c.get(2).unwrap().as_str().parse().unwrap_or(0.0);
-        let s: f64 = c.get(3).unwrap().as_str().parse().unwrap_or(0.0);
-        h * 3600.0 + m * 60.0 + s
-    } else {
-        return Err("ffmpeg probe: duration not found".to_string());
-    };
-
-    Ok(VideoProbe {
-        fps,
-        duration_sec,
-        creation_time: None,
-    })
+    parse_ffmpeg_banner(&stderr).map_err(|e| e.context("ffmpeg probe"))
 }
 
 pub async fn probe_video(
     ffprobe_bin: Option<&str>,
     ffmpeg_bin: &str,
     input: &str,
-) -> Result<VideoProbe, String> {
+) -> Result<VideoProbe, ProbeError> {
     if let Some(bin) = ffprobe_bin {
-        if let Ok(p) = probe_with_ffprobe(bin, input).await {
-            return Ok(p);
+        // A file ffprobe read fine but found no video in is not retried
+        match probe_with_ffprobe(bin, input).await {
+            Err(ProbeError::Failed(_)) => {}
+            result => return result,
         }
     }
     probe_with_ffmpeg(ffmpeg_bin, input).await
@@ -206,6 +378,15 @@ pub async fn probe_video(
 
 // ===== Conversion helpers =====
 
+/// `-threads` for one of `workers` parallel conversions, sharing the threads
+/// allowed by `cpu_limit`. `None` leaves a single full-speed conversion to ffmpeg.
+fn threads_per_worker(cpu_limit: Option<u8>, workers: usize) -> Option<usize> {
+    if cpu_limit == Some(100) && workers <= 1 {
+        return None;
+    }
+    Some((threads_from_cpu_limit(cpu_limit) / workers.max(1)).max(1))
+}
+
 fn threads_from_cpu_limit(cpu_limit: Option<u8>) -> usize {
     let max_cpus = num_cpus::get();
     match cpu_limit {
@@ -255,12 +436,13 @@ fn parse_progress_time(key: &str, val: &str) -> Option<f64> {
     None
 }
 
+/// Durations and frame counts are `None` when the source reports no duration
 struct Timings {
     setpts: f64,
     atempo: f64,
-    new_duration: f64,
-    progress_total_secs: f64,
-    total_frames_est: u64,
+    new_duration: Option<f64>,
+    progress_total_secs: Option<f64>,
+    total_frames_est: Option<u64>,
 }
 
 async fn compute_timings(probe: &VideoProbe, target_fps: f32) -> Result<Timings, AppError> {
@@ -275,9 +457,10 @@ async fn compute_timings(probe: &VideoProbe, target_fps: f32) -> Result<Timings,
     let setpts = (src_fps / tfps).max(0.00001);
     let atempo = (tfps / src_fps).max(0.00001);
 
-    let progress_total_secs = probe.duration_sec.max(0.000001);
-    let total_frames_est = (probe.duration_sec * src_fps).round().max(1.0) as u64;
-    let new_duration = probe.duration_sec * (src_fps / tfps);
+    let duration = probe.duration();
+    let progress_total_secs = duration;
+    let total_frames_est = duration.map(|d| (d * src_fps).round().max(1.0) as u64);
+    let new_duration = duration.map(|d| d * (src_fps / tfps));
 
     Ok(Timings {
         setpts,
@@ -288,12 +471,29 @@ async fn compute_timings(probe: &VideoProbe, target_fps: f32) -> Result<Timings,
     })
 }
 
-/// Build video encoding arguments with GPU support
+// Used instead of a target bitrate when the source duration is unknown
+const UNKNOWN_DURATION_CRF: u8 = 18;
+
+fn libx264_crf_args(crf: u8) -> Vec<String> {
+    vec![
+        "-c:v".into(),
+        "libx264".into(),
+        "-crf".into(),
+        crf.to_string(),
+        "-preset".into(),
+        "slow".into(),
+        "-pix_fmt".into(),
+        "yuv420p".into(),
+    ]
+}
+
+/// Build video encoding arguments with GPU support. Without a known
+/// `new_duration` no bitrate can be derived, so libx264 runs in CRF mode.
 async fn build_video_args(
-    input: &str,
+    inputs: &[&str],
     use_custom_quality: bool,
     crf: u8,
-    new_duration: f64,
+    new_duration: Option<f64>,
     use_gpu: bool,
     gpu_type: Option<&str>,
 ) -> Result<Vec<String>, AppError> {
@@ -306,8 +506,8 @@ async fn build_video_args(
     // GPU encoding - always use auto-bitrate mode to preserve quality
     // Custom CRF is only available for CPU encoding
     if use_gpu {
-        if let Some(gpu) = gpu_type {
-            let target_kbps = calculate_target_bitrate(input, new_duration).await?;
+        if let (Some(gpu), Some(new_duration)) = (gpu_type, new_duration) {
+            let target_kbps = calculate_target_bitrate(inputs, new_duration).await?;
             // Use slightly higher bitrate for GPU to ensure quality preservation
             let quality_kbps = (target_kbps as f64 * 1.1) as u64; // 10% higher for safety margin
 
@@ -393,20 +593,14 @@ async fn build_video_args(
 
     // CPU encoding with custom CRF quality
     if use_custom_quality {
-        return Ok(vec![
-            "-c:v".into(),
-            "libx264".into(),
-            "-crf".into(),
-            crf.to_string(),
-            "-preset".into(),
-            "slow".into(),
-            "-pix_fmt".into(),
-            "yuv420p".into(),
-        ]);
+        return Ok(libx264_crf_args(crf));
     }
+    let Some(new_duration) = new_duration else {
+        return Ok(libx264_crf_args(UNKNOWN_DURATION_CRF));
+    };
 
     // CPU auto-bitrate mode (fallback if GPU not available)
-    let target_kbps = calculate_target_bitrate(input, new_duration).await?;
+    let target_kbps = calculate_target_bitrate(inputs, new_duration).await?;
 
     Ok(vec![
         "-b:v".into(),
@@ -422,11 +616,16 @@ async fn build_video_args(
 
 /// Calculate target bitrate based on input file size and expected duration
 /// Returns video bitrate in kbps with a reasonable minimum floor
-async fn calculate_target_bitrate(input: &str, new_duration: f64) -> Result<u64, AppError> {
-    let meta = fs::metadata(input)
-        .await
-        .map_err(|e| AppError::new(AppErrorCode::ReadMetadataFailed, e.to_string()))?;
-    let size_bytes = meta.len() as f64;
+/// `inputs` holds every chapter of a joined recording, so their sizes add up
+async fn calculate_target_bitrate(inputs: &[&str], new_duration: f64) -> Result<u64, AppError> {
+    let mut size_bytes = 0.0;
+    for input in inputs {
+        let meta = fs::metadata(input)
+            .await
+            .map_err(|e| AppError::new(AppErrorCode::ReadMetadataFailed, e.to_string()))?;
+        size_bytes += meta.len() as f64;
+    }
+    let input = inputs.first().copied().unwrap_or_default();
 
     if size_bytes <= 0.0 {
         let _ = log_error(
@@ -483,7 +682,14 @@ async fn build_audio_args(
     ])
 }
 
-async fn creation_time_for_input(probe: &VideoProbe, input: &str) -> Option<String> {
+async fn creation_time_for_input(
+    probe: &VideoProbe,
+    input: &str,
+    known: Option<&str>,
+) -> Option<String> {
+    if let Some(ct) = known {
+        return Some(ct.to_string());
+    }
     if let Some(ct) = &probe.creation_time {
         return Some(ct.clone());
     }
@@ -494,22 +700,94 @@ async fn creation_time_for_input(probe: &VideoProbe, input: &str) -> Option<Stri
         .map(system_time_to_rfc3339_z)
 }
 
+/// Container/stream metadata written to the output: creation time, the
+/// provenance tag, user tags and, when the source has one, the start timecode
+/// retimed to the target rate. For MOV/MP4 the `-timecode` option makes the
+/// muxer write a matching `tmcd` track.
+fn build_metadata_args(
+    output: &str,
+    meta_creation_time: Option<&String>,
+    timecode: Option<&TimecodeInfo>,
+    provenance: Option<&ConversionProvenance>,
+    user_tag_args: Vec<String>,
+) -> Vec<String> {
+    let mut args: Vec<String> = Vec::new();
+    let ext = Path::new(output)
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_lowercase())
+        .unwrap_or_default();
+    if matches!(ext.as_str(), "mp4" | "mov" | "m4v") {
+        // Without it the MP4 muxer drops every key outside its fixed list
+        args.push("-movflags".into());
+        args.push("+use_metadata_tags".into());
+    }
+    if let Some(ct) = meta_creation_time {
+        args.push("-metadata".into());
+        args.push(format!(r#"creation_time={}"#, ct));
+    }
+    if let Some(tc) = timecode {
+        args.push("-timecode".into());
+        args.push(tc.start.clone());
+        // Override the source stream tag, which is copied along with the video stream
+        args.push("-metadata:s:v:0".into());
+        args.push(format!("timecode={}", tc.start));
+    }
+    if let Some(p) = provenance {
+        args.extend(p.metadata_args(output));
+    }
+    args.extend(user_tag_args);
+    args
+}
+
+/// Encoder name from the `-c:v` argument, "libx264" when not given.
+fn encoder_from_args(video_args: &[String]) -> String {
+    video_args
+        .iter()
+        .position(|a| a == "-c:v")
+        .and_then(|i| video_args.get(i + 1))
+        .cloned()
+        .unwrap_or_else(|| "libx264".to_string())
+}
+
+async fn build_provenance(
+    probe: &VideoProbe,
+    input: &str,
+    target_fps: f32,
+    speed_factor: f64,
+    video_args: &[String],
+) -> ConversionProvenance {
+    let source_hash = match quick_hash(Path::new(input)).await {
+        Ok(h) => h,
+        Err(e) => {
+            log_error("SourceHashFailed", &format!("input={} err={}", input, e)).await;
+            String::new()
+        }
+    };
+    ConversionProvenance::new(
+        probe.fps,
+        target_fps as f64,
+        speed_factor,
+        &encoder_from_args(video_args),
+        &source_hash,
+    )
+}
+
 fn build_command_preview(
     ffmpeg_bin: &str,
-    input: &str,
+    input_args: &[String],
     output: &str,
     target_fps: f32,
     setpts: f64,
     threads: Option<usize>,
     video_args: &[String],
     audio_args: &[String],
-    meta_creation_time: Option<&String>,
+    metadata_args: &[String],
 ) -> String {
     let mut parts: Vec<String> = Vec::new();
     parts.push(ffmpeg_bin.to_string());
     parts.push("-y".to_string());
-    parts.push("-i".to_string());
-    parts.push(quote_if_needed(input));
+    parts.extend(input_args.iter().map(|a| quote_if_needed(a)));
     parts.push("-vf".to_string());
     parts.push(format!("setpts={:.5}*PTS", setpts));
     parts.push("-r".to_string());
@@ -520,10 +798,7 @@ fn build_command_preview(
         parts.push("-threads".to_string());
         parts.push(t.to_string());
     }
-    if let Some(ct) = meta_creation_time {
-        parts.push("-metadata".to_string());
-        parts.push(format!(r#"creation_time={}"#, ct));
-    }
+    parts.extend(metadata_args.iter().cloned());
     parts.push("-progress".to_string());
     parts.push("pipe:1".to_string());
     parts.push("-nostats".to_string());
@@ -533,21 +808,22 @@ fn build_command_preview(
 
 fn build_ffmpeg_command(
     ffmpeg_bin: &str,
-    input: &str,
+    input_args: &[String],
     output: &str,
     target_fps: f32,
     setpts: f64,
     threads: Option<usize>,
     video_args: Vec<String>,
     audio_args: Vec<String>,
-    meta_creation_time: Option<String>,
+    metadata_args: Vec<String>,
 ) -> Command {
     let mut cmd = Command::new(ffmpeg_bin);
     apply_no_window(&mut cmd);
 
+    // Only ever replaces a stale partial; existing outputs are handled by the
+    // batch's conflict policy
     cmd.arg("-y")
-        .arg("-i")
-        .arg(input)
+        .args(input_args)
         .arg("-vf")
         .arg(format!("setpts={:.5}*PTS", setpts))
         .arg("-r")
@@ -559,9 +835,7 @@ fn build_ffmpeg_command(
         cmd.arg("-threads").arg(t.to_string());
     }
 
-    if let Some(ct) = meta_creation_time {
-        cmd.arg("-metadata").arg(format!(r#"creation_time={}"#, ct));
-    }
+    cmd.args(metadata_args);
 
     cmd.arg("-progress")
         .arg("pipe:1")
@@ -569,7 +843,9 @@ fn build_ffmpeg_command(
         .arg(output)
         .stdin(Stdio::null())
         .stdout(Stdio::piped())
-        .stderr(Stdio::null());
+        .stderr(Stdio::null())
+        // Stop writing the partial output when the conversion is dropped (timeout)
+        .kill_on_drop(true);
 
     cmd
 }
@@ -578,12 +854,12 @@ struct ProgressTracker {
     last_pct: f32,
     last_frame: Option<u64>,
     last_secs: Option<f64>,
-    total_frames_est: u64,
-    total_secs: f64,
+    total_frames_est: Option<u64>,
+    total_secs: Option<f64>,
 }
 
 impl ProgressTracker {
-    fn new(total_frames_est: u64, total_secs: f64) -> Self {
+    fn new(total_frames_est: Option<u64>, total_secs: Option<f64>) -> Self {
         Self {
             last_pct: 0.0,
             last_frame: None,
@@ -612,12 +888,12 @@ impl ProgressTracker {
 
     fn emit(&mut self) -> Option<f32> {
         let mut candidates: Vec<f64> = Vec::new();
-        if let Some(fr) = self.last_frame {
-            let pf = (fr as f64 / self.total_frames_est as f64).clamp(0.0, 0.999);
+        if let (Some(fr), Some(total)) = (self.last_frame, self.total_frames_est) {
+            let pf = (fr as f64 / total as f64).clamp(0.0, 0.999);
             candidates.push(pf);
         }
-        if let Some(s) = self.last_secs {
-            let pt = (s / self.total_secs).clamp(0.0, 0.999);
+        if let (Some(s), Some(total)) = (self.last_secs, self.total_secs) {
+            let pt = (s / total).clamp(0.0, 0.999);
             candidates.push(pt);
         }
         if candidates.is_empty() {
@@ -647,8 +923,80 @@ pub struct ConvertOptions<'a> {
     pub use_custom_video_quality: bool,
     pub video_quality: u8, // CRF 0..51
     pub cpu_limit: Option<u8>,
+    /// Conversions running at the same time; the `cpu_limit` threads are split between them
+    pub workers: usize,
     pub use_gpu: bool,
     pub gpu_type: Option<String>,
+    pub metadata: &'a [MetadataTag],
+    /// Recording time that takes precedence over the source's creation_time,
+    /// e.g. from a camera sidecar
+    pub creation_time: Option<&'a str>,
+    /// Further chapters of the recording, joined after `input`
+    pub chapters: &'a [String],
+    /// ffmpeg is suspended while this holds `true`
+    pub pause: Option<watch::Receiver<bool>>,
+}
+
+/// Resolves with the new pause state; never resolves without a pause channel.
+async fn pause_changed(pause: &mut Option<watch::Receiver<bool>>) -> bool {
+    let Some(rx) = pause else {
+        return std::future::pending().await;
+    };
+    if rx.changed().await.is_err() {
+        return std::future::pending().await;
+    }
+    *rx.borrow_and_update()
+}
+
+/// Stop or continue the ffmpeg process with SIGSTOP/SIGCONT.
+#[cfg(unix)]
+async fn set_suspended(child: &Child, suspended: bool) {
+    let Some(pid) = child.id() else {
+        return;
+    };
+    let signal = if suspended { libc::SIGSTOP } else { libc::SIGCONT };
+    // SAFETY: kill() only takes plain integers and touches no memory of ours.
+    // `child.id()` is `None` once tokio has reaped the process, so `pid` still
+    // names our unreaped child and cannot have been recycled.
+    let rc = unsafe { libc::kill(pid as libc::pid_t, signal) };
+    if rc == -1 {
+        let err = std::io::Error::last_os_error();
+        log_error("SuspendFfmpegFailed", &format!("pid={} signal={} {}", pid, signal, err))
+            .await;
+    }
+}
+
+#[cfg(not(unix))]
+async fn set_suspended(_child: &Child, _suspended: bool) {}
+
+/// Concat demuxer list in the temp folder, removed when dropped
+struct ConcatList(std::path::PathBuf);
+
+impl ConcatList {
+    async fn write(inputs: &[&str]) -> Result<Self, AppError> {
+        static COUNTER: AtomicU64 = AtomicU64::new(0);
+        let path = std::env::temp_dir().join(format!(
+            "freefps_concat_{}_{}.txt",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        let mut list = String::new();
+        for input in inputs {
+            let absolute = std::path::absolute(input)?;
+            list.push_str(&format!(
+                "file '{}'\n",
+                absolute.to_string_lossy().replace('\'', r"'\''")
+            ));
+        }
+        fs::write(&path, list).await?;
+        Ok(Self(path))
+    }
+}
+
+impl Drop for ConcatList {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
 }
 
 // Internal implementation with coded errors.
@@ -656,14 +1004,14 @@ async fn convert_video_with_progress_impl<F>(
     opts: ConvertOptions<'_>,
     mut on_progress: F,
     cancel: CancellationToken,
-) -> Result<Option<String>, AppError>
+) -> Result<ConvertOutcome, AppError>
 where
     F: FnMut(f32) + Send + 'static,
 {
     rotate_log_if_needed().await;
 
     // Probe
-    let probe = match probe_video(opts.ffprobe_bin, opts.ffmpeg_bin, opts.input).await {
+    let probe = match probe_video_cached(opts.ffprobe_bin, opts.ffmpeg_bin, opts.input).await {
         Ok(p) => p,
         Err(e) => {
             let code = if opts.ffprobe_bin.is_some() {
@@ -677,12 +1025,39 @@ where
         }
     };
 
+    // Chapters play back to back: progress and bitrate cover the combined duration
+    let mut probe = probe;
+    let mut inputs = vec![opts.input];
+    for chapter in opts.chapters {
+        let p = probe_video_cached(opts.ffprobe_bin, opts.ffmpeg_bin, chapter)
+            .await
+            .map_err(|e| AppError::new(AppErrorCode::FfprobeFailed, e))?;
+        probe.duration_sec += p.duration_sec;
+        inputs.push(chapter);
+    }
+    let concat_list = if opts.chapters.is_empty() {
+        None
+    } else {
+        Some(ConcatList::write(&inputs).await?)
+    };
+    let input_args: Vec<String> = match &concat_list {
+        Some(list) => vec![
+            "-f".into(),
+            "concat".into(),
+            "-safe".into(),
+            "0".into(),
+            "-i".into(),
+            list.0.to_string_lossy().to_string(),
+        ],
+        None => vec!["-i".into(), opts.input.to_string()],
+    };
+
     // Timings
     let timings = compute_timings(&probe, opts.target_fps).await?;
 
     // Args
     let video_args = build_video_args(
-        opts.input,
+        &inputs,
         opts.use_custom_video_quality,
         opts.video_quality,
         timings.new_duration,
@@ -693,108 +1068,348 @@ where
 
     let audio_args = build_audio_args(opts.keep_audio, opts.audio_bitrate, timings.atempo).await?;
 
-    let threads_opt = if opts.cpu_limit == Some(100) {
-        None
-    } else {
-        Some(threads_from_cpu_limit(opts.cpu_limit))
-    };
-    let meta_creation_time = creation_time_for_input(&probe, opts.input).await;
-
-    // Preview + log
-    let preview = build_command_preview(
-        opts.ffmpeg_bin,
+    let threads_opt = threads_per_worker(opts.cpu_limit, opts.workers);
+    let meta_creation_time = creation_time_for_input(&probe, opts.input, opts.creation_time).await;
+    let output_timecode = probe
+        .timecode
+        .as_ref()
+        .and_then(|tc| tc.retime(opts.target_fps as f64));
+    let provenance = build_provenance(
+        &probe,
         opts.input,
-        opts.output,
         opts.target_fps,
-        timings.setpts,
-        threads_opt,
+        timings.atempo,
         &video_args,
-        &audio_args,
-        meta_creation_time.as_ref(),
+    )
+    .await;
+    let user_tag_args = build_tag_args(
+        opts.metadata,
+        &TemplateContext {
+            source_path: opts.input,
+            source_fps: probe.fps,
+            target_fps: opts.target_fps,
+        },
     );
+    let metadata_for = |video_args: &[String]| {
+        // The provenance tag names the encoder of the attempt
+        let provenance = ConversionProvenance {
+            encoder: encoder_from_args(video_args),
+            ..provenance.clone()
+        };
+        build_metadata_args(
+            opts.output,
+            meta_creation_time.as_ref(),
+            output_timecode.as_ref(),
+            Some(&provenance),
+            user_tag_args.clone(),
+        )
+    };
+
+    // Hardware encoders can fail mid-batch (driver reset, session limit,
+    // unsupported resolution); such files are retried on the CPU
+    let fallback = if opts.use_gpu && !encoder_from_args(&video_args).starts_with("lib") {
+        Some(
+            build_video_args(
+                &inputs,
+                opts.use_custom_video_quality,
+                opts.video_quality,
+                timings.new_duration,
+                false,
+                None,
+            )
+            .await?,
+        )
+    } else {
+        None
+    };
+
+    // ffmpeg writes next to the output; the file is renamed once verified
+    let partial = PartialOutput::new(Path::new(opts.output));
+    let partial_str = partial.path().to_string_lossy().to_string();
+    let run = EncodeRun {
+        ffmpeg_bin: opts.ffmpeg_bin,
+        input_args: &input_args,
+        output: &partial_str,
+        target_fps: opts.target_fps,
+        setpts: timings.setpts,
+        threads: threads_opt,
+        audio_args: &audio_args,
+        total_frames_est: timings.total_frames_est,
+        total_secs: timings.progress_total_secs,
+    };
+    let preview = run.preview(&video_args, &metadata_for(&video_args));
     let _ = log_ffmpeg_command(&preview).await;
 
-    // Command
-    let mut cmd = build_ffmpeg_command(
-        opts.ffmpeg_bin,
-        opts.input,
-        opts.output,
-        opts.target_fps,
-        timings.setpts,
-        threads_opt,
-        video_args,
-        audio_args,
-        meta_creation_time.clone(),
-    );
+    let mut pause = opts.pause.clone();
+    let encoded = encode_with_fallback(
+        &run,
+        &video_args,
+        fallback.as_deref(),
+        metadata_for,
+        &mut on_progress,
+        &cancel,
+        &mut pause,
+    )
+    .await;
+
+    // Command of the CPU attempt, logged only when it ran
+    let fallback_preview = fallback
+        .as_deref()
+        .map(|args| run.preview(args, &metadata_for(args)))
+        .unwrap_or_default();
+    let hardware_failed = |e: &AppError| {
+        format!(
+            "{} failed ({}) (cmd: {})",
+            encoder_from_args(&video_args),
+            e.details.as_deref().unwrap_or(""),
+            preview
+        )
+    };
+    match encoded {
+        Ok(encoded) => {
+            if let Some(e) = encoded.hardware_error {
+                let emsg = format!("{}, retried with {}", hardware_failed(&e), encoded.encoder);
+                let _ = log_error("HardwareEncodeFailed", &emsg).await;
+                let _ = log_ffmpeg_command(&fallback_preview).await;
+            }
 
-    // Spawn
-    let mut child = match cmd.spawn() {
-        Ok(c) => c,
-        Err(e) => {
-            let emsg = format!("ffmpeg spawn failed: {}", e);
-            let _ = log_error("FfmpegSpawnFailed", &emsg).await;
-            return Err(AppError::new(
-                AppErrorCode::FfmpegSpawnFailed,
-                e.to_string(),
-            ));
+            // A clean exit is not enough: the output must probe as a video
+            let verified = probe_video(opts.ffprobe_bin, opts.ffmpeg_bin, &partial_str)
+                .await
+                .is_ok_and(|p| p.duration_sec > 0.0);
+            if !verified {
+                let _ = log_error("OutputVerificationFailed", &partial_str).await;
+                return Err(AppError::new(
+                    AppErrorCode::FfmpegFailed,
+                    "output failed verification",
+                ));
+            }
+            partial
+                .commit()
+                .map_err(|e| AppError::new(AppErrorCode::Io, e.to_string()))?;
+            Ok(ConvertOutcome {
+                creation_time: meta_creation_time,
+                encoder: encoded.encoder,
+            })
         }
-    };
+        Err(EncodeFailed {
+            error: e,
+            hardware_error,
+        }) => {
+            // Report the command of the attempt whose error is returned
+            let failed_preview = match &hardware_error {
+                Some(hw) => {
+                    let _ = log_error("HardwareEncodeFailed", &hardware_failed(hw)).await;
+                    &fallback_preview
+                }
+                None => &preview,
+            };
+            match e.code {
+                AppErrorCode::Cancelled => {
+                    let _ = log_error("Cancelled", "conversion cancelled by user").await;
+                }
+                AppErrorCode::FfmpegSpawnFailed => {
+                    let details = e.details.as_deref().unwrap_or("");
+                    let emsg = format!("ffmpeg spawn failed: {}", details);
+                    let _ = log_error("FfmpegSpawnFailed", &emsg).await;
+                }
+                AppErrorCode::FfmpegFailed => {
+                    let emsg = format!(
+                        "{} (cmd: {})",
+                        e.details.as_deref().unwrap_or(""),
+                        failed_preview
+                    );
+                    let _ = log_error("FfmpegFailed", &emsg).await;
+                }
+                _ => {}
+            }
+            Err(e)
+        }
+    }
+}
 
-    let mut stdout = tokio::io::BufReader::new(child.stdout.take().unwrap()).lines();
-    on_progress(0.0);
+/// Result of a finished conversion
+#[derive(Debug, Clone)]
+pub struct ConvertOutcome {
+    /// creation_time written to the output
+    pub creation_time: Option<String>,
+    /// Encoder that produced the output, "libx264" after a hardware fallback
+    pub encoder: String,
+}
 
-    // Progress tracking
-    let mut tracker = ProgressTracker::new(timings.total_frames_est, timings.progress_total_secs);
+/// Parts of the ffmpeg command line that stay the same between encode attempts
+struct EncodeRun<'a> {
+    ffmpeg_bin: &'a str,
+    input_args: &'a [String],
+    output: &'a str,
+    target_fps: f32,
+    setpts: f64,
+    threads: Option<usize>,
+    audio_args: &'a [String],
+    total_frames_est: Option<u64>,
+    total_secs: Option<f64>,
+}
 
-    loop {
-        tokio::select! {
-            _ = cancel.cancelled() => {
-                let _ = child.kill().await;
-                let _ = child.wait().await;
-                let _ = log_error("Cancelled", "conversion cancelled by user").await;
-                return Err(AppError::code_only(AppErrorCode::Cancelled));
-            }
-            line = stdout.next_line() => {
-                match line {
-                    Ok(Some(l)) => {
-                        if let Some((k,v)) = l.split_once('=') {
-                            if let Some(pct) = tracker.update_kv(k.trim(), v.trim()) {
-                                on_progress(pct);
-                            }
-                            // ffmpeg emits "progress=end"
-                            if k.trim() == "progress" && v.trim() == "end" {
-                                break;
+impl EncodeRun<'_> {
+    fn preview(&self, video_args: &[String], metadata_args: &[String]) -> String {
+        build_command_preview(
+            self.ffmpeg_bin,
+            self.input_args,
+            self.output,
+            self.target_fps,
+            self.setpts,
+            self.threads,
+            video_args,
+            self.audio_args,
+            metadata_args,
+        )
+    }
+
+    /// Run ffmpeg once; progress starts over at 0.
+    async fn run<F>(
+        &self,
+        video_args: &[String],
+        metadata_args: Vec<String>,
+        on_progress: &mut F,
+        cancel: &CancellationToken,
+        pause: &mut Option<watch::Receiver<bool>>,
+    ) -> Result<(), AppError>
+    where
+        F: FnMut(f32),
+    {
+        let mut cmd = build_ffmpeg_command(
+            self.ffmpeg_bin,
+            self.input_args,
+            self.output,
+            self.target_fps,
+            self.setpts,
+            self.threads,
+            video_args.to_vec(),
+            self.audio_args.to_vec(),
+            metadata_args,
+        );
+
+        // Spawn
+        let mut child = cmd
+            .spawn()
+            .map_err(|e| AppError::new(AppErrorCode::FfmpegSpawnFailed, e.to_string()))?;
+
+        let mut stdout = tokio::io::BufReader::new(child.stdout.take().unwrap()).lines();
+        on_progress(0.0);
+
+        // Paused while ffmpeg was starting
+        if pause.as_mut().is_some_and(|p| *p.borrow_and_update()) {
+            set_suspended(&child, true).await;
+        }
+
+        // Progress tracking
+        let mut tracker = ProgressTracker::new(self.total_frames_est, self.total_secs);
+
+        loop {
+            tokio::select! {
+                _ = cancel.cancelled() => {
+                    let _ = child.kill().await;
+                    let _ = child.wait().await;
+                    return Err(AppError::code_only(AppErrorCode::Cancelled));
+                }
+                paused = pause_changed(pause) => set_suspended(&child, paused).await,
+                line = stdout.next_line() => {
+                    match line {
+                        Ok(Some(l)) => {
+                            if let Some((k,v)) = l.split_once('=') {
+                                if let Some(pct) = tracker.update_kv(k.trim(), v.trim()) {
+                                    on_progress(pct);
+                                }
+                                // ffmpeg emits "progress=end"
+                                if k.trim() == "progress" && v.trim() == "end" {
+                                    break;
+                                }
                             }
                         }
-                    }
-                    Ok(None) => break,
-                    Err(e) => {
-                        let _ = log_error("ProgressReadFailed", &e.to_string()).await;
-                        break;
+                        Ok(None) => break,
+                        Err(e) => {
+                            let _ = log_error("ProgressReadFailed", &e.to_string()).await;
+                            break;
+                        }
                     }
                 }
             }
         }
+
+        let status = child
+            .wait()
+            .await
+            .map_err(|e| AppError::new(AppErrorCode::Io, format!("ffmpeg wait failed: {e}")))?;
+        if status.success() {
+            on_progress(100.0);
+            Ok(())
+        } else {
+            Err(AppError::new(
+                AppErrorCode::FfmpegFailed,
+                format!("ffmpeg failed with code {:?}", status.code()),
+            ))
+        }
     }
+}
 
-    let status = child
-        .wait()
-        .await
-        .map_err(|e| AppError::new(AppErrorCode::Io, format!("ffmpeg wait failed: {e}")))?;
-    if status.success() {
-        on_progress(100.0);
-        Ok(meta_creation_time)
-    } else {
-        let emsg = format!(
-            "ffmpeg failed with code {:?} (cmd: {})",
-            status.code(),
-            preview
-        );
-        let _ = log_error("FfmpegFailed", &emsg).await;
-        Err(AppError::new(
-            AppErrorCode::FfmpegFailed,
-            format!("ffmpeg failed with code {:?}", status.code()),
-        ))
+/// Result of [`encode_with_fallback`]
+struct Encoded {
+    encoder: String,
+    /// Why the hardware encoder failed, when the CPU fallback produced the output
+    hardware_error: Option<AppError>,
+}
+
+/// Error of [`encode_with_fallback`]
+#[derive(Debug)]
+struct EncodeFailed {
+    error: AppError,
+    /// Why the hardware encoder failed, when `error` comes from the CPU fallback
+    hardware_error: Option<AppError>,
+}
+
+/// Encode with `video_args`; when ffmpeg fails and `fallback` (the CPU args)
+/// is given, retry once with it. `metadata_for` builds the metadata args of
+/// each attempt.
+async fn encode_with_fallback<F, M>(
+    run: &EncodeRun<'_>,
+    video_args: &[String],
+    fallback: Option<&[String]>,
+    metadata_for: M,
+    on_progress: &mut F,
+    cancel: &CancellationToken,
+    pause: &mut Option<watch::Receiver<bool>>,
+) -> Result<Encoded, EncodeFailed>
+where
+    F: FnMut(f32),
+    M: Fn(&[String]) -> Vec<String>,
+{
+    let first = run
+        .run(video_args, metadata_for(video_args), on_progress, cancel, pause)
+        .await;
+    match (first, fallback) {
+        (Ok(()), _) => Ok(Encoded {
+            encoder: encoder_from_args(video_args),
+            hardware_error: None,
+        }),
+        (Err(e), Some(cpu_args)) if matches!(e.code, AppErrorCode::FfmpegFailed) => {
+            if let Err(error) = run
+                .run(cpu_args, metadata_for(cpu_args), on_progress, cancel, pause)
+                .await
+            {
+                return Err(EncodeFailed {
+                    error,
+                    hardware_error: Some(e),
+                });
+            }
+            Ok(Encoded {
+                encoder: encoder_from_args(cpu_args),
+                hardware_error: Some(e),
+            })
+        }
+        (Err(error), _) => Err(EncodeFailed {
+            error,
+            hardware_error: None,
+        }),
     }
 }
 
@@ -805,16 +1420,31 @@ pub async fn convert_video_with_progress<F>(
     opts: ConvertOptions<'_>,
     on_progress: F,
     cancel: CancellationToken,
-) -> Result<Option<String>, String>
+) -> Result<ConvertOutcome, String>
 where
     F: FnMut(f32) + Send + 'static,
 {
-    // Add timeout protection (3 hours default)
+    // Add timeout protection (3 hours default); time spent paused does not count
+    let mut pause = opts.pause.clone();
     let conversion_future = convert_video_with_progress_impl(opts, on_progress, cancel.clone());
-    let timeout_duration = Duration::from_secs(DEFAULT_CONVERSION_TIMEOUT_SECS);
+    tokio::pin!(conversion_future);
+    let mut remaining = Duration::from_secs(DEFAULT_CONVERSION_TIMEOUT_SECS);
+    let outcome = loop {
+        let paused = pause.as_mut().is_some_and(|p| *p.borrow_and_update());
+        let started = Instant::now();
+        tokio::select! {
+            result = &mut conversion_future => break Some(result),
+            _ = tokio::time::sleep(remaining), if !paused => break None,
+            _ = pause_changed(&mut pause) => {
+                if !paused {
+                    remaining = remaining.saturating_sub(started.elapsed());
+                }
+            }
+        }
+    };
 
-    match timeout(timeout_duration, conversion_future).await {
-        Ok(result) => match result {
+    match outcome {
+        Some(result) => match result {
             Ok(v) => Ok(v),
             Err(e) => {
                 if let AppErrorCode::Cancelled = e.code {
@@ -824,7 +1454,7 @@ where
                 }
             }
         },
-        Err(_) => {
+        None => {
             let _ = log_error(
                 "ConversionTimeout",
                 &format!(
@@ -837,4 +1467,131 @@ where
             Err((AppErrorCode::FfmpegFailed as u16).to_string())
         }
     }
-}
\ No newline at end of file
+}
+/// Write an executable shell `script` named `name` into `dir`, standing in
+/// for ffmpeg or ffprobe in tests.
+#[cfg(all(test, unix))]
+pub(crate) fn fake_tool(dir: &Path, name: &str, script: &str) -> String {
+    use std::os::unix::fs::PermissionsExt;
+    let path = dir.join(name);
+    std::fs::write(&path, script).unwrap();
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+    path.to_string_lossy().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Stand-in for ffmpeg that fails whenever `h264_nvenc` is requested and
+    /// appends the encoder of every call to "calls" next to itself.
+    #[cfg(unix)]
+    fn fake_ffmpeg(dir: &Path) -> String {
+        let script = r#"#!/bin/sh
+calls="$(dirname "$0")/calls"
+for a in "$@"; do
+  case "$a" in h264_nvenc|libx264) echo "$a" >> "$calls";; esac
+done
+for a in "$@"; do
+  [ "$a" = h264_nvenc ] && exit 1
+done
+echo progress=end
+"#;
+        fake_tool(dir, "ffmpeg", script)
+    }
+
+    #[test]
+    fn test_threads_per_worker() {
+        assert_eq!(threads_per_worker(Some(100), 1), None);
+        assert_eq!(threads_per_worker(Some(100), 0), None);
+        let all = num_cpus::get();
+        assert_eq!(threads_per_worker(Some(100), 2), Some((all / 2).max(1)));
+        for workers in [1, 2, all, all * 4, 1000] {
+            for limit in [None, Some(1), Some(50), Some(100)] {
+                let threads = threads_per_worker(limit, workers);
+                assert!(threads.is_none_or(|t| t >= 1 && t <= all));
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_unknown_duration_uses_crf() {
+        let banner = include_str!("../../tests/fixtures/ffmpeg_banner/h264_elementary_na.txt");
+        let probe = parse_ffmpeg_banner(banner).unwrap();
+        assert_eq!(probe.duration(), None);
+
+        let timings = compute_timings(&probe, 25.0).await.unwrap();
+        assert_eq!(timings.new_duration, None);
+        let mut tracker =
+            ProgressTracker::new(timings.total_frames_est, timings.progress_total_secs);
+        assert_eq!(tracker.update_kv("frame", "1"), None);
+
+        // Neither the GPU nor the CPU auto mode can derive a bitrate
+        for use_gpu in [false, true] {
+            let gpu = Some("nvidia");
+            let args = build_video_args(&[], false, 23, timings.new_duration, use_gpu, gpu)
+                .await
+                .unwrap();
+            assert_eq!(args, libx264_crf_args(UNKNOWN_DURATION_CRF));
+        }
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_hardware_encode_falls_back_to_cpu() {
+        let dir = tempfile::tempdir().unwrap();
+        let ffmpeg = fake_ffmpeg(dir.path());
+        let output = dir.path().join("out.mp4").to_string_lossy().to_string();
+        let input_args = vec!["-i".to_string(), "in.mp4".to_string()];
+        let run = EncodeRun {
+            ffmpeg_bin: &ffmpeg,
+            input_args: &input_args,
+            output: &output,
+            target_fps: 25.0,
+            setpts: 1.0,
+            threads: None,
+            audio_args: &[],
+            total_frames_est: Some(100),
+            total_secs: Some(4.0),
+        };
+        let args = |encoder: &str| vec!["-c:v".to_string(), encoder.to_string()];
+        let cancel = CancellationToken::new();
+
+        let mut progress = Vec::new();
+        let encoded = encode_with_fallback(
+            &run,
+            &args("h264_nvenc"),
+            Some(&args("libx264")),
+            |_| Vec::new(),
+            &mut |p| progress.push(p),
+            &cancel,
+            &mut None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(encoded.encoder, "libx264");
+        assert!(matches!(
+            encoded.hardware_error.map(|e| e.code),
+            Some(AppErrorCode::FfmpegFailed)
+        ));
+        assert_eq!(progress.last(), Some(&100.0));
+        let calls = std::fs::read_to_string(dir.path().join("calls")).unwrap();
+        assert_eq!(calls, "h264_nvenc\nlibx264\n");
+
+        // Without CPU args the hardware failure is returned
+        let err = encode_with_fallback(
+            &run,
+            &args("h264_nvenc"),
+            None,
+            |_| Vec::new(),
+            &mut |_| {},
+            &cancel,
+            &mut None,
+        )
+        .await
+        .err()
+        .unwrap();
+        assert!(matches!(err.error.code, AppErrorCode::FfmpegFailed));
+        assert!(err.hardware_error.is_none());
+    }
+}
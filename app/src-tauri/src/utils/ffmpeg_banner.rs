@@ -0,0 +1,333 @@
+// Free FPS - Video Frame Rate Converter
+// Copyright (C) 2025 undelalune
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+// Parser for the input banner that `ffmpeg -i <file>` prints to stderr.
+// Used as the probe fallback when ffprobe is not available or fails.
+
+use crate::utils::ffmpeg::{ProbeError, StreamInfo, VideoProbe};
+use regex::Regex;
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+fn stream_re() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    // "Stream #0:1[0x2](eng): Audio: ..." / "Stream #0:0(und): Video: ..." / "Stream #0:0: Video: ..."
+    RE.get_or_init(|| Regex::new(r"^Stream #\d+:(\d+)\S*: (\w+): (.*)$").unwrap())
+}
+
+fn duration_re() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"^Duration: (?:N/A|(\d+):(\d+):(\d+(?:[.,]\d+)?))").unwrap())
+}
+
+fn codec_tag_re() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    // "(avc1 / 0x31637661)"
+    RE.get_or_init(|| Regex::new(r"\(([^()/\s]+) / 0x[0-9A-Fa-f]+\)").unwrap())
+}
+
+fn resolution_re() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"^(\d+)x(\d+)(?:\s|$)").unwrap())
+}
+
+enum Section {
+    None,
+    InputMetadata,
+    StreamMetadata,
+    /// "Side data:", "Chapters:" and other blocks we do not need
+    Skipped,
+}
+
+/// Split a stream description on top-level ", " separators. Commas inside
+/// parentheses/brackets ("yuv420p(tv, bt709)") and decimal commas of localized
+/// builds ("29,97 fps") are kept.
+fn split_fields(s: &str) -> Vec<&str> {
+    let mut fields = Vec::new();
+    let mut depth = 0i32;
+    let mut start = 0;
+    let bytes = s.as_bytes();
+    for (i, &b) in bytes.iter().enumerate() {
+        match b {
+            b'(' | b'[' => depth += 1,
+            b')' | b']' => depth -= 1,
+            b',' if depth == 0 && bytes.get(i + 1) == Some(&b' ') => {
+                fields.push(s[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    fields.push(s[start..].trim());
+    fields
+}
+
+/// Parse a banner number such as "29.97", "29,97", "90k" or "1.2k".
+fn parse_number(s: &str) -> Option<f64> {
+    let s = s.trim().replace(',', ".");
+    match s.strip_suffix('k') {
+        Some(n) => n.parse::<f64>().ok().map(|v| v * 1000.0),
+        None => s.parse::<f64>().ok(),
+    }
+}
+
+/// The banner prints rates rounded to two decimals ("29.97", "23.98");
+/// map them back to the exact NTSC rational rates.
+fn snap_ntsc_rate(v: f64) -> f64 {
+    let nominal = v.round();
+    let ntsc = nominal * 1000.0 / 1001.0;
+    if nominal > 0.0 && (v - nominal).abs() > 0.005 && (v - ntsc).abs() < 0.006 {
+        ntsc
+    } else {
+        v
+    }
+}
+
+/// Value of a "<number> <unit>" field, e.g. "29.97 fps" with unit "fps".
+fn field_rate(fields: &[&str], unit: &str) -> Option<f64> {
+    fields.iter().find_map(|f| {
+        f.strip_suffix(unit)
+            .filter(|n| n.ends_with(' '))
+            .and_then(parse_number)
+    })
+}
+
+fn parse_stream(index: u32, kind: &str, desc: &str) -> StreamInfo {
+    let fields = split_fields(desc);
+    let first = fields.first().copied().unwrap_or_default();
+
+    let codec_name = first
+        .split([' ', '('])
+        .next()
+        .filter(|c| !c.is_empty())
+        .map(str::to_string);
+    let codec_tag = codec_tag_re()
+        .captures(first)
+        .and_then(|c| c.get(1))
+        .map(|m| m.as_str().to_string());
+
+    let mut info = StreamInfo {
+        index,
+        codec_type: kind.to_lowercase(),
+        codec_name,
+        codec_tag,
+        width: None,
+        height: None,
+        fps: None,
+        attached_pic: desc.contains("(attached pic)"),
+        timecode: None,
+    };
+
+    if info.codec_type == "video" {
+        if let Some(c) = fields.iter().find_map(|f| resolution_re().captures(f)) {
+            info.width = c.get(1).and_then(|m| m.as_str().parse().ok());
+            info.height = c.get(2).and_then(|m| m.as_str().parse().ok());
+        }
+        // Some demuxers only print the guessed rate ("25 tbr")
+        info.fps = field_rate(&fields, "fps")
+            .or_else(|| field_rate(&fields, "tbr"))
+            .filter(|f| *f > 0.0)
+            .map(snap_ntsc_rate);
+    }
+
+    info
+}
+
+fn parse_duration(line: &str) -> Option<f64> {
+    let c = duration_re().captures(line)?;
+    let (Some(h), Some(m), Some(s)) = (c.get(1), c.get(2), c.get(3)) else {
+        // Duration: N/A
+        return Some(0.0);
+    };
+    let h = parse_number(h.as_str())?;
+    let m = parse_number(m.as_str())?;
+    let s = parse_number(s.as_str())?;
+    Some(h * 3600.0 + m * 60.0 + s)
+}
+
+/// Parse the `ffmpeg -i` banner of the first input into a [`VideoProbe`].
+pub fn parse_ffmpeg_banner(stderr: &str) -> Result<VideoProbe, ProbeError> {
+    let mut streams: Vec<StreamInfo> = Vec::new();
+    let mut duration_sec: Option<f64> = None;
+    let mut format_tags: HashMap<String, String> = HashMap::new();
+    let mut section = Section::None;
+    let mut seen_input = false;
+
+    for raw in stderr.lines() {
+        let line = raw.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let indent = raw.len() - raw.trim_start().len();
+
+        if line.starts_with("Input #") {
+            if seen_input {
+                break;
+            }
+            seen_input = true;
+            section = Section::None;
+            continue;
+        }
+        if line.starts_with("Output #") || line.starts_with("Stream mapping:") {
+            break;
+        }
+
+        if line == "Metadata:" {
+            section = if indent <= 2 {
+                Section::InputMetadata
+            } else {
+                Section::StreamMetadata
+            };
+            continue;
+        }
+        if line.starts_with("Duration:") {
+            duration_sec = parse_duration(line);
+            section = Section::None;
+            continue;
+        }
+        if let Some(c) = stream_re().captures(line) {
+            let index = c[1].parse().unwrap_or(streams.len() as u32);
+            streams.push(parse_stream(index, &c[2], &c[3]));
+            section = Section::None;
+            continue;
+        }
+        if line.ends_with(':') && !line.contains(" :") || line.starts_with("Chapter #") {
+            section = Section::Skipped;
+            continue;
+        }
+
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let (key, value) = (key.trim(), value.trim());
+        match section {
+            Section::InputMetadata => {
+                format_tags.insert(key.to_string(), value.to_string());
+            }
+            Section::StreamMetadata => {
+                if key == "timecode" {
+                    if let Some(s) = streams.last_mut() {
+                        s.timecode = Some(value.to_string());
+                    }
+                }
+            }
+            Section::None | Section::Skipped => {}
+        }
+    }
+
+    if streams.is_empty() {
+        // Surface ffmpeg's own reason ("No such file or directory", "Invalid data found ...")
+        let reason = stderr
+            .lines()
+            .map(str::trim)
+            .rfind(|l| !l.is_empty() && !l.starts_with("At least one output file"))
+            .unwrap_or("no streams found");
+        return Err(ProbeError::Failed(reason.to_string()));
+    }
+
+    VideoProbe::from_streams(streams, duration_sec.unwrap_or(0.0), &format_tags)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    macro_rules! fixture {
+        ($name:literal) => {
+            include_str!(concat!("../../tests/fixtures/ffmpeg_banner/", $name, ".txt"))
+        };
+    }
+
+    fn approx(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-3
+    }
+
+    #[test]
+    fn test_iphone_hevc_mov() {
+        let p = parse_ffmpeg_banner(fixture!("iphone_hevc_mov")).unwrap();
+        assert!(approx(p.fps, 60000.0 / 1001.0));
+        assert!(approx(p.duration_sec, 12.48));
+        assert_eq!(p.creation_time.as_deref(), Some("2024-06-01T14:22:05.000000Z"));
+        assert_eq!(p.video_codec.as_deref(), Some("hevc"));
+        assert_eq!((p.width, p.height), (Some(3840), Some(2160)));
+        assert!(p.has_audio);
+        assert_eq!(p.streams.len(), 6);
+        assert!(p.timecode.is_none());
+    }
+
+    #[test]
+    fn test_prores_with_tmcd_track() {
+        let p = parse_ffmpeg_banner(fixture!("prores_tmcd_df")).unwrap();
+        assert!(approx(p.fps, 30000.0 / 1001.0));
+        assert_eq!(p.video_codec.as_deref(), Some("prores"));
+        let tc = p.timecode.unwrap();
+        assert_eq!(tc.start, "01:00:12;04");
+        assert!(tc.drop_frame);
+        let tmcd = p.streams.iter().find(|s| s.codec_tag.as_deref() == Some("tmcd"));
+        assert_eq!(tmcd.map(|s| s.codec_type.as_str()), Some("data"));
+    }
+
+    #[test]
+    fn test_tbr_only_stream() {
+        let p = parse_ffmpeg_banner(fixture!("mkv_tbr_only")).unwrap();
+        assert!(approx(p.fps, 25.0));
+        assert!(approx(p.duration_sec, 95.04));
+        assert_eq!((p.width, p.height), (Some(1280), Some(720)));
+    }
+
+    #[test]
+    fn test_attached_cover_art_is_skipped() {
+        let p = parse_ffmpeg_banner(fixture!("mp4_cover_art_first")).unwrap();
+        assert!(approx(p.fps, 24000.0 / 1001.0));
+        assert_eq!(p.video_codec.as_deref(), Some("h264"));
+        assert_eq!((p.width, p.height), (Some(1920), Some(800)));
+        assert!(p.streams.iter().any(|s| s.attached_pic));
+    }
+
+    #[test]
+    fn test_duration_not_available() {
+        let p = parse_ffmpeg_banner(fixture!("h264_elementary_na")).unwrap();
+        assert!(approx(p.fps, 50.0));
+        assert_eq!(p.duration_sec, 0.0);
+        assert!(!p.has_audio);
+    }
+
+    #[test]
+    fn test_localized_decimal_commas() {
+        let p = parse_ffmpeg_banner(fixture!("localized_decimal_comma")).unwrap();
+        assert!(approx(p.fps, 30000.0 / 1001.0));
+        assert!(approx(p.duration_sec, 62.5));
+        assert_eq!(p.timecode.map(|t| t.start).as_deref(), Some("00:59:58:10"));
+    }
+
+    #[test]
+    fn test_gopro_with_data_tracks() {
+        let p = parse_ffmpeg_banner(fixture!("gopro_hevc")).unwrap();
+        assert!(approx(p.fps, 240000.0 / 1001.0));
+        assert_eq!(p.timecode.map(|t| t.start).as_deref(), Some("14:03:27:11"));
+        assert_eq!(p.streams.iter().filter(|s| s.codec_type == "data").count(), 3);
+    }
+
+    #[test]
+    fn test_audio_only_and_missing_file() {
+        assert_eq!(
+            parse_ffmpeg_banner(fixture!("audio_only_m4a")).unwrap_err(),
+            ProbeError::NoVideoStream
+        );
+        let err = parse_ffmpeg_banner(fixture!("missing_file")).unwrap_err();
+        assert!(err.to_string().contains("No such file or directory"));
+    }
+}
@@ -0,0 +1,68 @@
+// Free FPS - Video Frame Rate Converter
+// Copyright (C) 2025 undelalune
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use sha2::{Digest, Sha256};
+use std::io::SeekFrom;
+use std::path::Path;
+use tokio::fs::File;
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+
+// Bytes read from each end of the file for the quick hash
+const SAMPLE_SIZE: u64 = 4 * 1024 * 1024;
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// SHA-256 over the file size plus its first and last 4 MiB.
+/// Cheap enough for multi-GB files on network mounts, and distinct for
+/// practically all real recordings.
+pub async fn quick_hash(path: &Path) -> std::io::Result<String> {
+    let mut file = File::open(path).await?;
+    let size = file.metadata().await?.len();
+
+    let mut hasher = Sha256::new();
+    hasher.update(size.to_le_bytes());
+
+    let mut buf = vec![0u8; SAMPLE_SIZE.min(size) as usize];
+    file.read_exact(&mut buf).await?;
+    hasher.update(&buf);
+
+    if size > SAMPLE_SIZE {
+        let tail_len = SAMPLE_SIZE.min(size - SAMPLE_SIZE);
+        file.seek(SeekFrom::Start(size - tail_len)).await?;
+        buf.resize(tail_len as usize, 0);
+        file.read_exact(&mut buf).await?;
+        hasher.update(&buf);
+    }
+
+    Ok(to_hex(&hasher.finalize()))
+}
+
+/// SHA-256 over the whole file.
+pub async fn full_hash(path: &Path) -> std::io::Result<String> {
+    let mut file = File::open(path).await?;
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0u8; 1024 * 1024];
+    loop {
+        let n = file.read(&mut buf).await?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Ok(to_hex(&hasher.finalize()))
+}
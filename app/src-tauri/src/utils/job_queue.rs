@@ -0,0 +1,255 @@
+// Free FPS - Video Frame Rate Converter
+// Copyright (C) 2025 undelalune
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+// Journal of the running batch in the app data dir. It is removed when the
+// batch ends, so a journal found at startup belongs to a batch that was
+// interrupted by a crash or by closing the app.
+
+use crate::commands::video::VideoConversionParams;
+use crate::utils::logger::log_error;
+use crate::utils::partial_output::partial_path;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use tauri::Manager;
+use tokio::fs;
+use tokio::sync::Mutex;
+
+static JOB_PATH: OnceLock<PathBuf> = OnceLock::new();
+/// Journal of the batch running in this process
+static CURRENT: Mutex<Option<BatchJob>> = Mutex::const_new(None);
+/// Interrupted batch found at startup, until it is resumed or discarded
+static INTERRUPTED: Mutex<Option<BatchJob>> = Mutex::const_new(None);
+
+// Bump when the journal layout changes; older journals are ignored
+const JOB_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum JobFileState {
+    Pending,
+    /// Being converted; after a crash its output is partial
+    Running,
+    Done,
+    Skipped,
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobEntry {
+    pub source: String,
+    /// Chapters joined after `source`
+    #[serde(default)]
+    pub chapters: Vec<String>,
+    /// Final output path; "" when the source was rejected before an output
+    /// path was derived
+    pub output: String,
+    pub state: JobFileState,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchJob {
+    version: u32,
+    /// RFC 3339
+    pub started_at: String,
+    pub params: VideoConversionParams,
+    pub files: Vec<JobEntry>,
+}
+
+impl BatchJob {
+    pub fn new(params: VideoConversionParams, files: Vec<JobEntry>) -> Self {
+        Self {
+            version: JOB_VERSION,
+            started_at: chrono::Utc::now().to_rfc3339(),
+            params,
+            files,
+        }
+    }
+
+    /// Delete the partial outputs of files that were being converted and put
+    /// them back in the queue.
+    pub fn recover(&mut self) {
+        for entry in &mut self.files {
+            if entry.state == JobFileState::Running {
+                if !entry.output.is_empty() {
+                    let _ = std::fs::remove_file(partial_path(Path::new(&entry.output)));
+                }
+                entry.state = JobFileState::Pending;
+            }
+        }
+    }
+
+    /// Index of the first file that was not converted yet.
+    pub fn first_unfinished(&self) -> Option<usize> {
+        self.files
+            .iter()
+            .position(|f| matches!(f.state, JobFileState::Pending | JobFileState::Running))
+    }
+
+    /// Parameters converting the unfinished files, in their original order.
+    pub fn resume_params(&self) -> VideoConversionParams {
+        let files = self
+            .files
+            .iter()
+            .filter(|f| f.state == JobFileState::Pending)
+            .flat_map(|f| std::iter::once(&f.source).chain(&f.chapters).cloned())
+            .collect();
+        VideoConversionParams {
+            files,
+            ..self.params.clone()
+        }
+    }
+}
+
+// Call this once at app startup to place the journal next to the log
+pub fn init_job_path(app: &tauri::AppHandle) {
+    if let Ok(dir) = app.path().app_data_dir() {
+        let _ = std::fs::create_dir_all(&dir);
+        let _ = JOB_PATH.set(dir.join("conversion_job.json"));
+    }
+}
+
+async fn save(job: &BatchJob) {
+    let Some(path) = JOB_PATH.get() else {
+        return;
+    };
+    let Ok(json) = serde_json::to_vec_pretty(job) else {
+        return;
+    };
+    // Write to a temp file first so a crash never leaves a truncated journal
+    let tmp = path.with_extension("json.tmp");
+    if let Err(e) = fs::write(&tmp, json).await {
+        log_error("JobJournalWriteFailed", &e.to_string()).await;
+        return;
+    }
+    if let Err(e) = fs::rename(&tmp, path).await {
+        log_error("JobJournalWriteFailed", &e.to_string()).await;
+    }
+}
+
+async fn load(path: &Path) -> Option<BatchJob> {
+    let bytes = fs::read(path).await.ok()?;
+    serde_json::from_slice::<BatchJob>(&bytes)
+        .ok()
+        .filter(|j| j.version == JOB_VERSION)
+}
+
+/// Start journaling a batch. It replaces the journal of an interrupted one.
+pub async fn begin(job: BatchJob) {
+    *INTERRUPTED.lock().await = None;
+    save(&job).await;
+    *CURRENT.lock().await = Some(job);
+}
+
+/// Record the state of the file at `index` of the running batch.
+pub async fn set_state(index: usize, state: JobFileState) {
+    let mut guard = CURRENT.lock().await;
+    if let Some(entry) = guard.as_mut().and_then(|j| j.files.get_mut(index)) {
+        entry.state = state;
+    }
+    if let Some(job) = guard.as_ref() {
+        save(job).await;
+    }
+}
+
+/// The batch ended (finished, failed or cancelled): drop the journal.
+pub async fn finish() {
+    *CURRENT.lock().await = None;
+    discard().await;
+}
+
+/// Call this once at app startup, after `init_job_path`: picks up the
+/// journal of an interrupted batch and removes its partial outputs.
+pub async fn recover_interrupted() {
+    let Some(path) = JOB_PATH.get() else {
+        return;
+    };
+    let Some(mut job) = load(path).await else {
+        return;
+    };
+    job.recover();
+    if job.first_unfinished().is_none() {
+        discard().await;
+        return;
+    }
+    save(&job).await;
+    *INTERRUPTED.lock().await = Some(job);
+}
+
+/// Interrupted batch with unfinished files found at startup. `None` while a
+/// batch runs in this process.
+pub async fn load_interrupted() -> Option<BatchJob> {
+    if CURRENT.lock().await.is_some() {
+        return None;
+    }
+    INTERRUPTED.lock().await.clone()
+}
+
+pub async fn discard() {
+    *INTERRUPTED.lock().await = None;
+    if let Some(path) = JOB_PATH.get() {
+        let _ = fs::remove_file(path).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recover_and_resume() {
+        let dir = tempfile::tempdir().unwrap();
+        let partial = partial_path(&dir.path().join("b_24fps.mp4"));
+        std::fs::write(&partial, b"half").unwrap();
+
+        let params: VideoConversionParams = serde_json::from_str(
+            r#"{"input_folder":"/in","output_folder":"","target_fps":24,"cpu_limit":75,
+                "keep_audio":true,"audio_bitrate":192,"use_custom_video_quality":false,
+                "video_quality":23,"files":[]}"#,
+        )
+        .unwrap();
+        let entry = |source: &str, output: &Path, state| JobEntry {
+            source: source.into(),
+            chapters: Vec::new(),
+            output: output.to_string_lossy().to_string(),
+            state,
+        };
+        let mut job = BatchJob::new(
+            params,
+            vec![
+                entry(
+                    "/in/a.mp4",
+                    &dir.path().join("a_24fps.mp4"),
+                    JobFileState::Done,
+                ),
+                entry(
+                    "/in/b.mp4",
+                    &dir.path().join("b_24fps.mp4"),
+                    JobFileState::Running,
+                ),
+                entry(
+                    "/in/c.mp4",
+                    &dir.path().join("c_24fps.mp4"),
+                    JobFileState::Pending,
+                ),
+            ],
+        );
+
+        job.recover();
+        assert!(!partial.exists());
+        assert_eq!(job.first_unfinished(), Some(1));
+        assert_eq!(job.resume_params().files, ["/in/b.mp4", "/in/c.mp4"]);
+    }
+}
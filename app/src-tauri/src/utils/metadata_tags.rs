@@ -0,0 +1,248 @@
+// Free FPS - Video Frame Rate Converter
+// Copyright (C) 2025 undelalune
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+// User-defined output metadata tags with template variables.
+
+use crate::utils::provenance::PROVENANCE_KEY;
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Variables usable in tag values, e.g. "{source_name} @ {target_fps}fps"
+const TEMPLATE_VARS: [&str; 5] = [
+    "source_name",
+    "source_stem",
+    "source_fps",
+    "target_fps",
+    "date",
+];
+
+/// Keys the app writes itself
+const RESERVED_KEYS: [&str; 3] = ["creation_time", "timecode", PROVENANCE_KEY];
+
+/// AVI stores global tags in its RIFF INFO chunk, which only knows these keys.
+/// Its comment holds the provenance tag.
+const AVI_GLOBAL_KEYS: [&str; 10] = [
+    "title",
+    "artist",
+    "album",
+    "copyright",
+    "date",
+    "genre",
+    "language",
+    "track",
+    "encoder",
+    "encoded_by",
+];
+const AVI_STREAM_KEYS: [&str; 1] = ["title"];
+const MP4_STREAM_KEYS: [&str; 3] = ["title", "language", "handler_name"];
+const MPEGTS_GLOBAL_KEYS: [&str; 3] = ["title", "service_name", "service_provider"];
+const MPEGTS_STREAM_KEYS: [&str; 1] = ["language"];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MetadataTag {
+    pub key: String,
+    pub value: String,
+    /// Stream specifier ("v", "a", "s", optionally with an index like "a:1").
+    /// The tag is global when not set.
+    #[serde(default)]
+    pub stream: Option<String>,
+}
+
+/// Values available to tag templates for one file.
+pub struct TemplateContext<'a> {
+    pub source_path: &'a str,
+    pub source_fps: f64,
+    pub target_fps: f32,
+}
+
+/// Keys a container can store. `None` means any key.
+fn supported_keys(ext: &str, per_stream: bool) -> Option<&'static [&'static str]> {
+    match (ext, per_stream) {
+        ("avi", false) => Some(&AVI_GLOBAL_KEYS),
+        ("avi", true) => Some(&AVI_STREAM_KEYS),
+        // Custom global keys are stored because outputs are muxed with `use_metadata_tags`
+        ("mp4" | "mov" | "m4v", false) => None,
+        ("mp4" | "mov" | "m4v", true) => Some(&MP4_STREAM_KEYS),
+        ("ts" | "mts" | "m2ts", false) => Some(&MPEGTS_GLOBAL_KEYS),
+        ("ts" | "mts" | "m2ts", true) => Some(&MPEGTS_STREAM_KEYS),
+        _ => None,
+    }
+}
+
+fn is_valid_stream_specifier(spec: &str) -> bool {
+    let (kind, index) = match spec.split_once(':') {
+        Some((k, i)) => (k, Some(i)),
+        None => (spec, None),
+    };
+    matches!(kind, "v" | "a" | "s") && index.is_none_or(|i| i.parse::<u32>().is_ok())
+}
+
+fn template_vars(value: &str) -> impl Iterator<Item = &str> {
+    value
+        .split('{')
+        .skip(1)
+        .filter_map(|s| s.split_once('}'))
+        .map(|(v, _)| v)
+}
+
+/// Container-independent checks: key syntax, reserved keys, stream
+/// specifiers and template variables.
+pub fn validate_tags(tags: &[MetadataTag]) -> Result<(), String> {
+    for tag in tags {
+        let key = tag.key.trim();
+        if key.is_empty() || key.contains(['=', ' ', '\t', '\n']) {
+            return Err(format!("Invalid metadata key '{}'", tag.key));
+        }
+        if RESERVED_KEYS.iter().any(|r| r.eq_ignore_ascii_case(key)) {
+            return Err(format!("Metadata key '{}' is set by the app", key));
+        }
+        if let Some(spec) = &tag.stream {
+            if !is_valid_stream_specifier(spec) {
+                return Err(format!("Invalid stream specifier '{}' for '{}'", spec, key));
+            }
+        }
+        if let Some(var) = template_vars(&tag.value).find(|v| !TEMPLATE_VARS.contains(v)) {
+            return Err(format!(
+                "Unknown template variable '{{{}}}' in '{}'",
+                var, key
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Check that an output container with extension `ext` can store every tag.
+pub fn validate_tags_for_container(tags: &[MetadataTag], ext: &str) -> Result<(), String> {
+    let ext = ext.to_lowercase();
+    for tag in tags {
+        let key = tag.key.trim();
+        // AVI has no custom keys, so the provenance tag takes the comment
+        // (see `ConversionProvenance::metadata_args`)
+        if ext == "avi" && tag.stream.is_none() && key.eq_ignore_ascii_case("comment") {
+            return Err("AVI metadata key 'comment' is set by the app".to_string());
+        }
+        if let Some(allowed) = supported_keys(&ext, tag.stream.is_some()) {
+            if !allowed.iter().any(|k| k.eq_ignore_ascii_case(key)) {
+                let scope = if tag.stream.is_some() {
+                    "stream"
+                } else {
+                    "global"
+                };
+                return Err(format!(
+                    "{} does not support {} metadata key '{}' (supported: {})",
+                    ext.to_uppercase(),
+                    scope,
+                    key,
+                    allowed.join(", ")
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn format_fps(fps: f64) -> String {
+    let s = format!("{:.3}", fps);
+    s.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+pub fn render_value(template: &str, ctx: &TemplateContext) -> String {
+    let path = Path::new(ctx.source_path);
+    let name = path
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string();
+    let stem = path
+        .file_stem()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string();
+
+    template
+        .replace("{source_name}", &name)
+        .replace("{source_stem}", &stem)
+        .replace("{source_fps}", &format_fps(ctx.source_fps))
+        .replace("{target_fps}", &format_fps(ctx.target_fps as f64))
+        .replace("{date}", &Local::now().format("%Y-%m-%d").to_string())
+}
+
+/// `-metadata` / `-metadata:s:<spec>` arguments for the tags with templates rendered.
+pub fn build_tag_args(tags: &[MetadataTag], ctx: &TemplateContext) -> Vec<String> {
+    let mut args = Vec::new();
+    for tag in tags {
+        match &tag.stream {
+            Some(spec) => args.push(format!("-metadata:s:{}", spec)),
+            None => args.push("-metadata".to_string()),
+        }
+        args.push(format!(
+            "{}={}",
+            tag.key.trim(),
+            render_value(&tag.value, ctx)
+        ));
+    }
+    args
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tag(key: &str, value: &str, stream: Option<&str>) -> MetadataTag {
+        MetadataTag {
+            key: key.into(),
+            value: value.into(),
+            stream: stream.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_validation() {
+        assert!(validate_tags(&[tag("title", "{source_stem} ({source_fps} fps)", None)]).is_ok());
+        assert!(validate_tags(&[tag("title", "{unknown}", None)]).is_err());
+        assert!(validate_tags(&[tag("creation_time", "x", None)]).is_err());
+        assert!(validate_tags(&[tag("language", "eng", Some("a:1"))]).is_ok());
+        assert!(validate_tags(&[tag("language", "eng", Some("x"))]).is_err());
+
+        let custom = [tag("shot_id", "A001", None)];
+        assert!(validate_tags_for_container(&custom, "mkv").is_ok());
+        assert!(validate_tags_for_container(&custom, "MP4").is_ok());
+        assert!(validate_tags_for_container(&custom, "avi").is_err());
+        assert!(validate_tags_for_container(&[tag("comment", "x", None)], "avi").is_err());
+        assert!(validate_tags_for_container(&[tag("comment", "x", None)], "mkv").is_ok());
+        assert!(validate_tags_for_container(&[tag("handler_name", "x", Some("v"))], "mov").is_ok());
+        assert!(validate_tags_for_container(&[tag("comment", "x", Some("v"))], "mov").is_err());
+    }
+
+    #[test]
+    fn test_render() {
+        let ctx = TemplateContext {
+            source_path: "/footage/A001_C004.mov",
+            source_fps: 60000.0 / 1001.0,
+            target_fps: 24.0,
+        };
+        let args = build_tag_args(
+            &[tag(
+                "comment",
+                "{source_name} {source_fps}->{target_fps}",
+                None,
+            )],
+            &ctx,
+        );
+        assert_eq!(args, vec!["-metadata", "comment=A001_C004.mov 59.94->24"]);
+    }
+}
@@ -1,5 +1,17 @@
 pub mod bundled_ffmpeg;
+pub mod camera_card;
+pub mod chapters;
 pub mod ffmpeg;
+pub mod ffmpeg_banner;
 pub mod gpu;
+pub mod hashing;
+pub mod job_queue;
 pub mod logger;
+pub mod metadata_tags;
+pub mod partial_output;
+pub mod probe_cache;
+pub mod provenance;
 pub mod rate_limiter;
+pub mod scan_filter;
+pub mod sniff;
+pub mod timecode;
@@ -0,0 +1,162 @@
+// Free FPS - Video Frame Rate Converter
+// Copyright (C) 2025 undelalune
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+// Conversions write to a ".partial" file next to the output and rename it
+// once ffmpeg succeeded, so an interrupted conversion never leaves a file
+// that looks finished. Partials being written are listed in the app data
+// dir, and the ones a crash left behind are removed at the next start.
+
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use tauri::Manager;
+
+static LIST_PATH: OnceLock<PathBuf> = OnceLock::new();
+/// Partials being written by this process
+static ACTIVE: Mutex<Vec<PathBuf>> = Mutex::new(Vec::new());
+
+/// "clip_24fps.partial.mp4" for "clip_24fps.mp4". The extension is kept so
+/// ffmpeg still picks the muxer from it.
+pub fn partial_path(output: &Path) -> PathBuf {
+    let stem = output.file_stem().unwrap_or_default().to_string_lossy();
+    let name = match output.extension() {
+        Some(ext) => format!("{}.partial.{}", stem, ext.to_string_lossy()),
+        None => format!("{}.partial", stem),
+    };
+    output.with_file_name(name)
+}
+
+/// True for names made by [`partial_path`]; scans never list them.
+pub fn is_partial_path(path: &Path) -> bool {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    stem.ends_with(".partial") || path.extension().is_some_and(|e| e == "partial")
+}
+
+fn save(active: &[PathBuf]) {
+    let Some(path) = LIST_PATH.get() else {
+        return;
+    };
+    if active.is_empty() {
+        let _ = std::fs::remove_file(path);
+        return;
+    }
+    if let Ok(json) = serde_json::to_vec(active) {
+        let _ = std::fs::write(path, json);
+    }
+}
+
+// Call this once at app startup, before any conversion runs
+pub fn init_partial_list(app: &tauri::AppHandle) {
+    if let Ok(dir) = app.path().app_data_dir() {
+        let _ = std::fs::create_dir_all(&dir);
+        let _ = LIST_PATH.set(dir.join("partial_outputs.json"));
+    }
+    sweep_stale();
+}
+
+/// Delete the partials listed by a previous run of the app.
+fn sweep_stale() {
+    let Some(path) = LIST_PATH.get() else {
+        return;
+    };
+    let Ok(bytes) = std::fs::read(path) else {
+        return;
+    };
+    let stale: Vec<PathBuf> = serde_json::from_slice(&bytes).unwrap_or_default();
+    for partial in stale {
+        let _ = std::fs::remove_file(partial);
+    }
+    let _ = std::fs::remove_file(path);
+}
+
+/// Temporary output of one conversion. Removed when dropped without
+/// [`PartialOutput::commit`], e.g. on cancel, failure or timeout.
+pub struct PartialOutput {
+    path: PathBuf,
+    output: PathBuf,
+    committed: bool,
+}
+
+impl PartialOutput {
+    pub fn new(output: &Path) -> Self {
+        let path = partial_path(output);
+        let mut active = ACTIVE.lock().unwrap_or_else(|e| e.into_inner());
+        active.push(path.clone());
+        save(&active);
+        Self {
+            path,
+            output: output.to_path_buf(),
+            committed: false,
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Move the finished partial to the output path, replacing an older output.
+    pub fn commit(mut self) -> std::io::Result<()> {
+        std::fs::rename(&self.path, &self.output)?;
+        self.committed = true;
+        Ok(())
+    }
+}
+
+impl Drop for PartialOutput {
+    fn drop(&mut self) {
+        if !self.committed {
+            let _ = std::fs::remove_file(&self.path);
+        }
+        let mut active = ACTIVE.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(i) = active.iter().position(|p| *p == self.path) {
+            active.swap_remove(i);
+        }
+        save(&active);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_partial_output() {
+        assert_eq!(
+            partial_path(Path::new("/out/clip_24fps.mp4")),
+            Path::new("/out/clip_24fps.partial.mp4")
+        );
+        assert!(is_partial_path(&partial_path(Path::new("/out/clip_24fps.mp4"))));
+        assert!(is_partial_path(&partial_path(Path::new("/out/clip_24fps"))));
+        assert!(!is_partial_path(Path::new("/out/partial.mp4")));
+
+        let dir = tempfile::tempdir().unwrap();
+        let output = dir.path().join("a_24fps.mp4");
+        std::fs::write(&output, b"old").unwrap();
+
+        // Dropped without commit: the partial goes, the old output stays
+        let partial = PartialOutput::new(&output);
+        std::fs::write(partial.path(), b"half").unwrap();
+        let partial_file = partial.path().to_path_buf();
+        drop(partial);
+        assert!(!partial_file.exists());
+        assert_eq!(std::fs::read(&output).unwrap(), b"old");
+
+        let partial = PartialOutput::new(&output);
+        std::fs::write(partial.path(), b"new").unwrap();
+        partial.commit().unwrap();
+        assert!(!partial_file.exists());
+        assert_eq!(std::fs::read(&output).unwrap(), b"new");
+    }
+}
@@ -0,0 +1,252 @@
+// Free FPS - Video Frame Rate Converter
+// Copyright (C) 2025 undelalune
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+// Persistent cache of probe results, so folders on slow network mounts are
+// not re-probed on every scan and conversion.
+
+use crate::utils::ffmpeg::{probe_video, ProbeError, VideoProbe};
+use crate::utils::logger::log_error;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+use std::time::{Duration, UNIX_EPOCH};
+use tauri::Manager;
+use tokio::fs;
+use tokio::sync::Mutex;
+
+static CACHE_PATH: OnceLock<PathBuf> = OnceLock::new();
+static CACHE: Mutex<Option<CacheFile>> = Mutex::const_new(None);
+/// Held while the cache file is written, so a flush and a clear never interleave
+static WRITE: Mutex<()> = Mutex::const_new(());
+static FLUSH_SCHEDULED: AtomicBool = AtomicBool::new(false);
+static PRUNING: AtomicBool = AtomicBool::new(false);
+
+// Bump when the `VideoProbe` layout changes so stale entries are dropped
+const CACHE_VERSION: u32 = 2;
+const MAX_ENTRIES: usize = 20_000;
+// New entries are written together once probing has been quiet this long
+const FLUSH_DELAY: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CacheEntry {
+    size: u64,
+    mtime_ms: u128,
+    cached_at: i64,
+    probe: VideoProbe,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CacheFile {
+    version: u32,
+    // Keyed by canonical path
+    entries: HashMap<String, CacheEntry>,
+}
+
+// Call this once at app startup to place the cache next to the log
+pub fn init_cache_path(app: &tauri::AppHandle) {
+    if let Ok(dir) = app.path().app_data_dir() {
+        let _ = std::fs::create_dir_all(&dir);
+        let _ = CACHE_PATH.set(dir.join("probe_cache.json"));
+    }
+}
+
+fn cache_path() -> Option<&'static PathBuf> {
+    CACHE_PATH.get()
+}
+
+async fn load() -> CacheFile {
+    let Some(path) = cache_path() else {
+        return CacheFile::default();
+    };
+    match fs::read(path).await {
+        Ok(bytes) => serde_json::from_slice::<CacheFile>(&bytes)
+            .ok()
+            .filter(|c| c.version == CACHE_VERSION)
+            .unwrap_or_default(),
+        Err(_) => CacheFile::default(),
+    }
+}
+
+async fn save(path: &Path, json: Vec<u8>) {
+    // Write to a temp file first so a crash never leaves a truncated cache
+    let tmp = path.with_extension("json.tmp");
+    if let Err(e) = fs::write(&tmp, json).await {
+        log_error("ProbeCacheWriteFailed", &e.to_string()).await;
+        return;
+    }
+    if let Err(e) = fs::rename(&tmp, path).await {
+        log_error("ProbeCacheWriteFailed", &e.to_string()).await;
+    }
+}
+
+/// Write the cache to disk. The lock is only held while serializing.
+async fn flush() {
+    let _write = WRITE.lock().await;
+    FLUSH_SCHEDULED.store(false, Ordering::SeqCst);
+    let Some(path) = cache_path() else {
+        return;
+    };
+    let json = {
+        let guard = CACHE.lock().await;
+        match guard.as_ref().map(serde_json::to_vec) {
+            Some(Ok(json)) => json,
+            _ => return,
+        }
+    };
+    save(path, json).await;
+}
+
+/// Write entries still waiting for the debounced flush. Call this when the
+/// app exits.
+pub async fn flush_probe_cache() {
+    if FLUSH_SCHEDULED.load(Ordering::SeqCst) {
+        flush().await;
+    }
+}
+
+/// Flush the cache after [`FLUSH_DELAY`] unless a flush is already pending,
+/// so a scan probing many files writes the cache once instead of per file.
+fn schedule_flush() {
+    if FLUSH_SCHEDULED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    tokio::spawn(async {
+        tokio::time::sleep(FLUSH_DELAY).await;
+        flush().await;
+    });
+}
+
+/// Cache key parts of a file: canonical path, size and modification time.
+async fn file_identity(input: &str) -> Option<(String, u64, u128)> {
+    let canonical = fs::canonicalize(input).await.ok()?;
+    let meta = fs::metadata(&canonical).await.ok()?;
+    let mtime_ms = meta
+        .modified()
+        .ok()?
+        .duration_since(UNIX_EPOCH)
+        .ok()?
+        .as_millis();
+    Some((canonical.to_string_lossy().to_string(), meta.len(), mtime_ms))
+}
+
+/// Drop entries of files that no longer exist, then the oldest ones.
+async fn prune() {
+    if PRUNING.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    let keys: Vec<String> = match CACHE.lock().await.as_ref() {
+        Some(c) if c.entries.len() > MAX_ENTRIES => c.entries.keys().cloned().collect(),
+        _ => {
+            PRUNING.store(false, Ordering::SeqCst);
+            return;
+        }
+    };
+    // Checked without the lock, this takes a while on network mounts
+    let mut gone = Vec::new();
+    for key in keys {
+        if !fs::try_exists(&key).await.unwrap_or(false) {
+            gone.push(key);
+        }
+    }
+
+    let mut guard = CACHE.lock().await;
+    if let Some(cache) = guard.as_mut() {
+        for key in &gone {
+            cache.entries.remove(key);
+        }
+        prune_oldest(cache);
+    }
+    PRUNING.store(false, Ordering::SeqCst);
+}
+
+fn prune_oldest(cache: &mut CacheFile) {
+    if cache.entries.len() > MAX_ENTRIES {
+        let mut by_age: Vec<(String, i64)> = cache
+            .entries
+            .iter()
+            .map(|(k, e)| (k.clone(), e.cached_at))
+            .collect();
+        by_age.sort_by_key(|(_, t)| *t);
+        let excess = cache.entries.len() - MAX_ENTRIES;
+        for (k, _) in by_age.into_iter().take(excess) {
+            cache.entries.remove(&k);
+        }
+    }
+}
+
+/// Same as [`probe_video`], served from the cache while the file's size and
+/// modification time are unchanged.
+pub async fn probe_video_cached(
+    ffprobe_bin: Option<&str>,
+    ffmpeg_bin: &str,
+    input: &str,
+) -> Result<VideoProbe, ProbeError> {
+    let Some((key, size, mtime_ms)) = file_identity(input).await else {
+        return probe_video(ffprobe_bin, ffmpeg_bin, input).await;
+    };
+
+    {
+        let mut guard = CACHE.lock().await;
+        if guard.is_none() {
+            *guard = Some(load().await);
+        }
+        if let Some(entry) = guard.as_ref().and_then(|c| c.entries.get(&key)) {
+            if entry.size == size && entry.mtime_ms == mtime_ms {
+                return Ok(entry.probe.clone());
+            }
+        }
+    }
+
+    let probe = probe_video(ffprobe_bin, ffmpeg_bin, input).await?;
+
+    {
+        let mut guard = CACHE.lock().await;
+        let cache = guard.get_or_insert_with(CacheFile::default);
+        cache.version = CACHE_VERSION;
+        cache.entries.insert(
+            key,
+            CacheEntry {
+                size,
+                mtime_ms,
+                cached_at: chrono::Utc::now().timestamp(),
+                probe: probe.clone(),
+            },
+        );
+    }
+    prune().await;
+    schedule_flush();
+
+    Ok(probe)
+}
+
+/// Drop all cached probe results, in memory and on disk.
+pub async fn clear_probe_cache() -> std::io::Result<()> {
+    let _write = WRITE.lock().await;
+    *CACHE.lock().await = Some(CacheFile {
+        version: CACHE_VERSION,
+        entries: HashMap::new(),
+    });
+    if let Some(path) = cache_path() {
+        match fs::remove_file(path).await {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,159 @@
+// Free FPS - Video Frame Rate Converter
+// Copyright (C) 2025 undelalune
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+// Provenance tag written into every output, describing how it was produced.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Metadata key of the provenance tag
+pub const PROVENANCE_KEY: &str = "free_fps";
+/// Marks the tag value, so it is also recognized when a container
+/// only allows storing it in the `comment` field
+const VALUE_PREFIX: &str = "free-fps:";
+/// Length of the source hash kept in the tag
+const HASH_PREFIX_LEN: usize = 16;
+
+/// Conversion mode: frames are kept and re-timed (setpts), audio is tempo-adjusted
+pub const MODE_RETIME: &str = "retime";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConversionProvenance {
+    pub source_fps: f64,
+    pub target_fps: f64,
+    pub mode: String,
+    /// Playback speed of the output relative to the source (target / source fps)
+    pub speed_factor: f64,
+    pub encoder: String,
+    pub app_version: String,
+    /// Prefix of the source's quick hash (see `hashing::quick_hash`)
+    pub source_hash: String,
+    /// Unix time the conversion started. Outputs get the source's file
+    /// times, so their mtime can't tell when they were made.
+    #[serde(default)]
+    pub converted_at: Option<i64>,
+}
+
+impl ConversionProvenance {
+    pub fn new(
+        source_fps: f64,
+        target_fps: f64,
+        speed_factor: f64,
+        encoder: &str,
+        source_hash: &str,
+    ) -> Self {
+        Self {
+            source_fps,
+            target_fps,
+            mode: MODE_RETIME.to_string(),
+            speed_factor,
+            encoder: encoder.to_string(),
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+            source_hash: source_hash.chars().take(HASH_PREFIX_LEN).collect(),
+            converted_at: Some(chrono::Utc::now().timestamp()),
+        }
+    }
+
+    pub fn to_tag_value(&self) -> String {
+        format!(
+            "{}{}",
+            VALUE_PREFIX,
+            serde_json::to_string(self).unwrap_or_default()
+        )
+    }
+
+    pub fn from_tag_value(value: &str) -> Option<Self> {
+        let json = value.trim().strip_prefix(VALUE_PREFIX)?;
+        serde_json::from_str(json).ok()
+    }
+
+    /// Find the provenance tag among container tags. Keys are compared
+    /// case-insensitively since Matroska tags are often upper-cased.
+    pub fn from_tags(tags: &HashMap<String, String>) -> Option<Self> {
+        tags.iter()
+            .filter(|(k, _)| {
+                k.eq_ignore_ascii_case(PROVENANCE_KEY) || k.eq_ignore_ascii_case("comment")
+            })
+            .find_map(|(_, v)| Self::from_tag_value(v))
+    }
+
+    /// True when the conversion started after the source was last modified.
+    pub fn newer_than(&self, source_mtime: std::time::SystemTime) -> bool {
+        let Ok(since_epoch) = source_mtime.duration_since(std::time::UNIX_EPOCH) else {
+            return false;
+        };
+        self.converted_at
+            .is_some_and(|t| t >= 0 && since_epoch.as_secs() <= t as u64)
+    }
+
+    /// True when the tag was written for a source with this quick hash.
+    pub fn matches_source(&self, source_hash: &str) -> bool {
+        !self.source_hash.is_empty() && source_hash.starts_with(&self.source_hash)
+    }
+
+    /// ffmpeg arguments that store the tag in `output`. AVI only keeps its
+    /// INFO fields, so the tag goes into the comment there. MP4/MOV need
+    /// `use_metadata_tags` for the custom key (see `build_metadata_args`).
+    pub fn metadata_args(&self, output: &str) -> Vec<String> {
+        let is_avi = Path::new(output)
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| e.eq_ignore_ascii_case("avi"));
+        let key = if is_avi { "comment" } else { PROVENANCE_KEY };
+        vec![
+            "-metadata".into(),
+            format!("{}={}", key, self.to_tag_value()),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tag_round_trip() {
+        let p = ConversionProvenance::new(59.94, 29.97, 0.5, "libx264", "0123456789abcdef0123");
+        assert_eq!(p.source_hash, "0123456789abcdef");
+
+        let mut tags = HashMap::new();
+        tags.insert("FREE_FPS".to_string(), p.to_tag_value());
+        assert_eq!(ConversionProvenance::from_tags(&tags), Some(p.clone()));
+
+        let mut tags = HashMap::new();
+        tags.insert("comment".to_string(), "just a comment".to_string());
+        assert_eq!(ConversionProvenance::from_tags(&tags), None);
+        tags.insert("comment".to_string(), p.to_tag_value());
+        assert_eq!(ConversionProvenance::from_tags(&tags), Some(p));
+    }
+
+    #[test]
+    fn test_newer_than_source() {
+        use std::time::{Duration, SystemTime};
+        let p = ConversionProvenance::new(59.94, 29.97, 0.5, "libx264", "");
+        let hour = Duration::from_secs(3600);
+        assert!(p.newer_than(SystemTime::now() - hour));
+        assert!(!p.newer_than(SystemTime::now() + hour));
+
+        // Tags written before the conversion time was recorded
+        let old = ConversionProvenance {
+            converted_at: None,
+            ..p
+        };
+        assert!(!old.newer_than(SystemTime::now() - hour));
+    }
+}
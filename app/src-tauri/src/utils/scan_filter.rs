@@ -0,0 +1,239 @@
+// Free FPS - Video Frame Rate Converter
+// Copyright (C) 2025 undelalune
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+// Include/exclude rules applied while scanning, so large folders are
+// filtered in the backend instead of in the UI.
+
+use crate::utils::ffmpeg::VideoProbe;
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use serde::{Deserialize, Serialize};
+
+/// Filter spec sent by the UI. Unset bounds and empty lists match everything.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScanFilter {
+    /// Globs a file must match (any of them), e.g. "Day1/**" or "*.mov".
+    /// Matched case-insensitively against the file name and the path
+    /// relative to the scanned folder.
+    pub include: Vec<String>,
+    /// Globs that drop a file, e.g. "*_proxy*"
+    pub exclude: Vec<String>,
+    pub min_size: Option<u64>,
+    pub max_size: Option<u64>,
+    /// Seconds
+    pub min_duration: Option<f64>,
+    pub max_duration: Option<f64>,
+    pub min_fps: Option<f64>,
+    pub max_fps: Option<f64>,
+    pub min_width: Option<u32>,
+    pub max_width: Option<u32>,
+    pub min_height: Option<u32>,
+    pub max_height: Option<u32>,
+    /// Allowed video codecs as ffprobe names ("h264", "hevc", "prores")
+    pub codecs: Vec<String>,
+}
+
+/// A [`ScanFilter`] with its globs compiled.
+pub struct CompiledFilter {
+    include: Option<GlobSet>,
+    exclude: GlobSet,
+    spec: ScanFilter,
+}
+
+fn build_globset(patterns: &[String]) -> Result<GlobSet, String> {
+    let mut builder = GlobSetBuilder::new();
+    for p in patterns {
+        let glob = GlobBuilder::new(p.trim())
+            .case_insensitive(true)
+            .build()
+            .map_err(|e| format!("Invalid pattern '{}': {}", p, e))?;
+        builder.add(glob);
+    }
+    builder.build().map_err(|e| e.to_string())
+}
+
+fn out_of_range<T: PartialOrd + Copy + std::fmt::Display>(
+    what: &str,
+    value: T,
+    min: Option<T>,
+    max: Option<T>,
+) -> Option<String> {
+    if let Some(min) = min.filter(|m| value < *m) {
+        return Some(format!("{} {} is below {}", what, value, min));
+    }
+    if let Some(max) = max.filter(|m| value > *m) {
+        return Some(format!("{} {} is above {}", what, value, max));
+    }
+    None
+}
+
+impl ScanFilter {
+    /// True when the filter has probe-based predicates.
+    pub fn needs_probe(&self) -> bool {
+        self.min_duration.is_some()
+            || self.max_duration.is_some()
+            || self.min_fps.is_some()
+            || self.max_fps.is_some()
+            || self.min_width.is_some()
+            || self.max_width.is_some()
+            || self.min_height.is_some()
+            || self.max_height.is_some()
+            || !self.codecs.is_empty()
+    }
+
+    pub fn compile(&self) -> Result<CompiledFilter, String> {
+        let include = if self.include.is_empty() {
+            None
+        } else {
+            Some(build_globset(&self.include)?)
+        };
+        // Catch typos early; an inverted range would silently drop every file
+        let ranges_ok = self.min_size.zip(self.max_size).is_none_or(|(a, b)| a <= b)
+            && self
+                .min_duration
+                .zip(self.max_duration)
+                .is_none_or(|(a, b)| a <= b)
+            && self.min_fps.zip(self.max_fps).is_none_or(|(a, b)| a <= b)
+            && self
+                .min_width
+                .zip(self.max_width)
+                .is_none_or(|(a, b)| a <= b)
+            && self
+                .min_height
+                .zip(self.max_height)
+                .is_none_or(|(a, b)| a <= b);
+        if !ranges_ok {
+            return Err("Filter minimum is greater than its maximum".to_string());
+        }
+        Ok(CompiledFilter {
+            include,
+            exclude: build_globset(&self.exclude)?,
+            spec: self.clone(),
+        })
+    }
+}
+
+impl CompiledFilter {
+    /// True when a probe is needed to evaluate the filter.
+    pub fn needs_probe(&self) -> bool {
+        self.spec.needs_probe()
+    }
+
+    /// Name, path and size checks. Returns why the file was rejected.
+    /// `relative_path` uses '/' separators.
+    pub fn reject_file(&self, name: &str, relative_path: &str, size: u64) -> Option<String> {
+        let matches = |set: &GlobSet| set.is_match(name) || set.is_match(relative_path);
+        if let Some(include) = &self.include {
+            if !matches(include) {
+                return Some("does not match the include patterns".to_string());
+            }
+        }
+        if matches(&self.exclude) {
+            return Some("matches an exclude pattern".to_string());
+        }
+        out_of_range("size", size, self.spec.min_size, self.spec.max_size)
+    }
+
+    /// Probe-based checks. Returns why the file was rejected.
+    pub fn reject_probe(&self, probe: &VideoProbe) -> Option<String> {
+        let s = &self.spec;
+        // Unknown duration (0) can't satisfy a duration bound
+        if let Some(r) = out_of_range(
+            "duration",
+            probe.duration_sec,
+            s.min_duration,
+            s.max_duration,
+        ) {
+            return Some(r);
+        }
+        if let Some(r) = out_of_range("fps", probe.fps, s.min_fps, s.max_fps) {
+            return Some(r);
+        }
+        let (width, height) = (probe.width.unwrap_or(0), probe.height.unwrap_or(0));
+        if let Some(r) = out_of_range("width", width, s.min_width, s.max_width) {
+            return Some(r);
+        }
+        if let Some(r) = out_of_range("height", height, s.min_height, s.max_height) {
+            return Some(r);
+        }
+        if !s.codecs.is_empty() {
+            let codec = probe.video_codec.as_deref().unwrap_or_default();
+            if !s.codecs.iter().any(|c| c.eq_ignore_ascii_case(codec)) {
+                return Some(format!("codec '{}' is not allowed", codec));
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::ffmpeg::test_probe;
+
+    #[test]
+    fn test_globs_and_size() {
+        let f = ScanFilter {
+            include: vec!["Day1/**".into()],
+            exclude: vec!["*_proxy*".into()],
+            min_size: Some(10),
+            ..ScanFilter::default()
+        }
+        .compile()
+        .unwrap();
+        assert!(f.reject_file("A.mov", "Day1/CamA/A.mov", 100).is_none());
+        assert!(f
+            .reject_file("a_PROXY.mov", "Day1/CamA/a_PROXY.mov", 100)
+            .is_some());
+        assert!(f.reject_file("B.mov", "Day2/B.mov", 100).is_some());
+        assert!(f.reject_file("A.mov", "Day1/A.mov", 5).is_some());
+        assert!(!f.needs_probe());
+
+        assert!(ScanFilter {
+            exclude: vec!["[".into()],
+            ..ScanFilter::default()
+        }
+        .compile()
+        .is_err());
+    }
+
+    #[test]
+    fn test_probe_predicates() {
+        let f = ScanFilter {
+            min_fps: Some(50.0),
+            min_duration: Some(5.0),
+            ..ScanFilter::default()
+        }
+        .compile()
+        .unwrap();
+        assert!(f.needs_probe());
+        assert!(f.reject_probe(&test_probe(59.94, 12.0, "hevc")).is_none());
+        assert!(f.reject_probe(&test_probe(29.97, 12.0, "hevc")).is_some());
+        assert!(f.reject_probe(&test_probe(59.94, 3.0, "hevc")).is_some());
+        // Unknown duration
+        assert!(f.reject_probe(&test_probe(59.94, 0.0, "hevc")).is_some());
+
+        let f = ScanFilter {
+            codecs: vec!["ProRes".into()],
+            min_height: Some(2160),
+            ..ScanFilter::default()
+        }
+        .compile()
+        .unwrap();
+        let err = f.reject_probe(&test_probe(25.0, 1.0, "prores")).unwrap();
+        assert!(err.contains("height"));
+    }
+}
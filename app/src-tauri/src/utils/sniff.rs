@@ -0,0 +1,113 @@
+// Free FPS - Video Frame Rate Converter
+// Copyright (C) 2025 undelalune
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+// Container detection from the first bytes of a file, for videos without
+// a known extension.
+
+use std::path::Path;
+use tokio::io::AsyncReadExt;
+
+/// Enough for two MPEG-TS/M2TS packets
+const HEADER_LEN: usize = 400;
+
+/// Read the first bytes of a file. Shorter files return what they have.
+pub async fn read_header(path: &Path) -> std::io::Result<Vec<u8>> {
+    let mut file = tokio::fs::File::open(path).await?;
+    let mut buf = vec![0u8; HEADER_LEN];
+    let mut len = 0;
+    while len < HEADER_LEN {
+        let n = file.read(&mut buf[len..]).await?;
+        if n == 0 {
+            break;
+        }
+        len += n;
+    }
+    buf.truncate(len);
+    Ok(buf)
+}
+
+/// Container family of a file header, or `None` if it is not a known video container.
+pub fn sniff_container(h: &[u8]) -> Option<&'static str> {
+    // ISO BMFF / QuickTime: size, then an atom type
+    if h.len() >= 8 && matches!(&h[4..8], b"ftyp" | b"moov" | b"mdat" | b"wide" | b"free") {
+        return Some("mp4");
+    }
+    if h.starts_with(&[0x1A, 0x45, 0xDF, 0xA3]) {
+        return Some("matroska");
+    }
+    if h.len() >= 12 && &h[0..4] == b"RIFF" && &h[8..12] == b"AVI " {
+        return Some("avi");
+    }
+    if h.starts_with(&[0x30, 0x26, 0xB2, 0x75, 0x8E, 0x66, 0xCF, 0x11]) {
+        return Some("asf");
+    }
+    if h.starts_with(b"FLV") {
+        return Some("flv");
+    }
+    // MXF partition pack key (SMPTE UL prefix)
+    if h.starts_with(&[0x06, 0x0E, 0x2B, 0x34]) {
+        return Some("mxf");
+    }
+    if h.starts_with(&[0x00, 0x00, 0x01, 0xBA]) {
+        return Some("mpeg");
+    }
+    // Transport streams: sync byte every 188 bytes, M2TS adds a 4-byte timestamp
+    if h.len() >= 189 && h[0] == 0x47 && h[188] == 0x47 {
+        return Some("mpegts");
+    }
+    if h.len() >= 197 && h[4] == 0x47 && h[196] == 0x47 {
+        return Some("mpegts");
+    }
+    None
+}
+
+/// File extension ffmpeg picks the muxer of a sniffed container from.
+pub fn container_extension(container: &str) -> &'static str {
+    match container {
+        "matroska" => "mkv",
+        "avi" => "avi",
+        "asf" => "wmv",
+        "flv" => "flv",
+        "mxf" => "mxf",
+        "mpeg" => "mpg",
+        "mpegts" => "ts",
+        _ => "mp4",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sniff_container() {
+        assert_eq!(
+            sniff_container(b"\0\0\0\x20ftypisom\0\0\x02\0"),
+            Some("mp4")
+        );
+        assert_eq!(sniff_container(b"\x1A\x45\xDF\xA3\x01\0"), Some("matroska"));
+        assert_eq!(sniff_container(b"RIFF\x10\0\0\0AVI LIST"), Some("avi"));
+
+        let mut ts = vec![0u8; 400];
+        ts[0] = 0x47;
+        ts[188] = 0x47;
+        assert_eq!(sniff_container(&ts), Some("mpegts"));
+
+        assert_eq!(sniff_container(b"RIFF\x10\0\0\0WAVEfmt "), None);
+        assert_eq!(sniff_container(b"plain text"), None);
+        assert_eq!(sniff_container(b""), None);
+    }
+}
@@ -0,0 +1,204 @@
+// Free FPS - Video Frame Rate Converter
+// Copyright (C) 2025 undelalune
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use serde::{Deserialize, Serialize};
+
+/// SMPTE start timecode of a clip, as read from a QuickTime `tmcd` track
+/// or a `timecode` tag.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TimecodeInfo {
+    /// Formatted start timecode, e.g. "01:00:00:00" or "01:00:00;00" (drop-frame)
+    pub start: String,
+    /// Timecode rate in frames per second (e.g. 29.97)
+    pub rate: f64,
+    pub drop_frame: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Timecode {
+    hours: u32,
+    minutes: u32,
+    seconds: u32,
+    frames: u32,
+}
+
+/// Nominal (integer) frame count per timecode second: 30 for 29.97, 24 for 23.976.
+fn nominal_rate(rate: f64) -> u32 {
+    (rate.round() as u32).max(1)
+}
+
+/// True for the NTSC rates where drop-frame counting exists (29.97 and 59.94).
+fn supports_drop_frame(rate: f64) -> bool {
+    let nominal = rate.round();
+    (nominal == 30.0 || nominal == 60.0) && (rate - nominal * 1000.0 / 1001.0).abs() < 0.01
+}
+
+/// Frames dropped at the start of every minute that is not a multiple of ten.
+fn dropped_per_minute(nominal: u32) -> u32 {
+    nominal / 15
+}
+
+fn parse_timecode(s: &str) -> Option<(Timecode, bool)> {
+    let s = s.trim();
+    // Drop-frame timecodes use ';' (or '.') before the frame field
+    let drop_frame = s.contains(';') || s.contains('.');
+    let parts: Vec<&str> = s.split([':', ';', '.']).collect();
+    if parts.len() != 4 {
+        return None;
+    }
+    let nums: Vec<u32> = parts
+        .iter()
+        .map(|p| p.trim().parse::<u32>().ok())
+        .collect::<Option<Vec<_>>>()?;
+    if nums[1] > 59 || nums[2] > 59 {
+        return None;
+    }
+    Some((
+        Timecode {
+            hours: nums[0],
+            minutes: nums[1],
+            seconds: nums[2],
+            frames: nums[3],
+        },
+        drop_frame,
+    ))
+}
+
+fn format_timecode(tc: &Timecode, drop_frame: bool) -> String {
+    let sep = if drop_frame { ';' } else { ':' };
+    format!(
+        "{:02}:{:02}:{:02}{}{:02}",
+        tc.hours, tc.minutes, tc.seconds, sep, tc.frames
+    )
+}
+
+/// Drop-frame labels ;00 and ;01 (;00..;03 at 59.94) do not exist outside every
+/// tenth minute; move such labels forward to the first valid frame.
+fn normalize_drop_frame(tc: &mut Timecode, nominal: u32) {
+    let drop = dropped_per_minute(nominal);
+    if tc.seconds == 0 && !tc.minutes.is_multiple_of(10) && tc.frames < drop {
+        tc.frames = drop;
+    }
+}
+
+impl TimecodeInfo {
+    /// Build from a raw timecode tag value and the rate of its track.
+    pub fn from_tag(value: &str, rate: f64) -> Option<Self> {
+        if rate <= 0.0 {
+            return None;
+        }
+        let (tc, tagged_drop) = parse_timecode(value)?;
+        let drop_frame = tagged_drop && supports_drop_frame(rate);
+        if tc.frames >= nominal_rate(rate) {
+            return None;
+        }
+        Some(Self {
+            start: format_timecode(&tc, drop_frame),
+            rate,
+            drop_frame,
+        })
+    }
+
+    /// Retime the start timecode to a new frame rate.
+    ///
+    /// Conforming keeps the HH:MM:SS of the source label and rescales the frame
+    /// field, so a clip that starts at 01:00:00:00 still starts there at the new
+    /// rate. Drop-frame counting is used for 29.97/59.94 targets: inherited from
+    /// an NTSC source, and the broadcast default otherwise.
+    pub fn retime(&self, target_fps: f64) -> Option<Self> {
+        if target_fps <= 0.0 {
+            return None;
+        }
+        let (mut tc, _) = parse_timecode(&self.start)?;
+        let src_nominal = nominal_rate(self.rate);
+        let dst_nominal = nominal_rate(target_fps);
+
+        tc.frames = ((tc.frames as u64 * dst_nominal as u64) / src_nominal as u64) as u32;
+        tc.frames = tc.frames.min(dst_nominal - 1);
+
+        let drop_frame = if supports_drop_frame(target_fps) {
+            if supports_drop_frame(self.rate) {
+                self.drop_frame
+            } else {
+                true
+            }
+        } else {
+            false
+        };
+        if drop_frame {
+            normalize_drop_frame(&mut tc, dst_nominal);
+        }
+
+        Some(Self {
+            start: format_timecode(&tc, drop_frame),
+            rate: target_fps,
+            drop_frame,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_format() {
+        let tc = TimecodeInfo::from_tag("01:00:00;00", 30000.0 / 1001.0).unwrap();
+        assert!(tc.drop_frame);
+        assert_eq!(tc.start, "01:00:00;00");
+
+        // Drop-frame separator on a non-NTSC rate is treated as non-drop
+        let tc = TimecodeInfo::from_tag("10:20:30;12", 25.0).unwrap();
+        assert!(!tc.drop_frame);
+        assert_eq!(tc.start, "10:20:30:12");
+
+        assert!(TimecodeInfo::from_tag("00:00:00:30", 30.0).is_none());
+        assert!(TimecodeInfo::from_tag("garbage", 30.0).is_none());
+    }
+
+    #[test]
+    fn test_retime_keeps_seconds_and_scales_frames() {
+        let tc = TimecodeInfo::from_tag("01:02:03:59", 60.0).unwrap();
+        let r = tc.retime(30.0).unwrap();
+        assert_eq!(r.start, "01:02:03:29");
+        assert!(!r.drop_frame);
+
+        let r = tc.retime(24.0).unwrap();
+        assert_eq!(r.start, "01:02:03:23");
+    }
+
+    #[test]
+    fn test_retime_drop_frame() {
+        // Non-NTSC source conformed to 29.97 defaults to drop-frame
+        let tc = TimecodeInfo::from_tag("00:01:00:00", 120.0).unwrap();
+        let r = tc.retime(29.97).unwrap();
+        assert!(r.drop_frame);
+        // 00:01:00;00 does not exist in drop-frame, first valid label is ;02
+        assert_eq!(r.start, "00:01:00;02");
+
+        // NDF 59.94 source keeps its counting mode at 29.97
+        let tc = TimecodeInfo::from_tag("00:10:00:00", 60000.0 / 1001.0).unwrap();
+        let r = tc.retime(30000.0 / 1001.0).unwrap();
+        assert!(!r.drop_frame);
+        assert_eq!(r.start, "00:10:00:00");
+
+        // 59.94 DF drops four frames per minute
+        let tc = TimecodeInfo::from_tag("00:01:00;00", 60000.0 / 1001.0);
+        assert!(tc.is_some());
+        let r = tc.unwrap().retime(59.94).unwrap();
+        assert_eq!(r.start, "00:01:00;04");
+    }
+}
@@ -1,16 +1,61 @@
 import {invoke} from '@tauri-apps/api/core';
 import {listen} from '@tauri-apps/api/event';
-import {ConversionProgress, GpuInfo, LicenseType, VideoConversionParams, VideoFile} from '@/types';
+import {
+    BatchJob,
+    ConversionProgress,
+    ConversionProvenance,
+    FolderSummary,
+    GpuInfo,
+    LicenseType,
+    ScanFileEvent,
+    ScanOptions,
+    ScanResult,
+    ScanSummary,
+    VideoConversionParams,
+    VideoProbe,
+    WatchFileEvent,
+    WatchStatus
+} from '@/types';
 
 export const tauriAPI = {
-    async getVideoFiles(folderPath: string): Promise<VideoFile[]> {
-        return await invoke<VideoFile[]>('get_video_files', {folderPath});
+    async getVideoFiles(folderPath: string, options?: ScanOptions, outputFolder?: string): Promise<ScanResult> {
+        return await invoke<ScanResult>('get_video_files', {folderPath, options, outputFolder});
+    },
+
+    async scanVideoFiles(
+        folderPath: string,
+        scanId: number,
+        options?: ScanOptions,
+        withProbe?: boolean,
+        outputFolder?: string
+    ): Promise<ScanSummary> {
+        return await invoke<ScanSummary>('scan_video_files', {folderPath, scanId, options, withProbe, outputFolder});
+    },
+
+    async cancelScan(): Promise<void> {
+        await invoke('cancel_scan');
+    },
+
+    async getFolderSummary(folderPath: string, options?: ScanOptions, targetFps?: number): Promise<FolderSummary> {
+        return await invoke<FolderSummary>('get_folder_summary', {folderPath, options, targetFps});
     },
 
     async getVideoThumbnail(path: string): Promise<string> {
         return await invoke<string>('get_video_thumbnail', {path});
     },
 
+    async getVideoProbe(path: string): Promise<VideoProbe> {
+        return await invoke<VideoProbe>('get_video_probe', {path});
+    },
+
+    async readConversionProvenance(path: string): Promise<ConversionProvenance | null> {
+        return await invoke<ConversionProvenance | null>('read_conversion_provenance', {path});
+    },
+
+    async clearProbeCache(): Promise<void> {
+        await invoke('clear_probe_cache');
+    },
+
     async convertVideos(params: VideoConversionParams): Promise<string> {
         return await invoke<string>('convert_videos', {params});
     },
@@ -19,6 +64,44 @@ export const tauriAPI = {
         await invoke('cancel_conversion');
     },
 
+    // Skip one file of the running batch; false when it is not part of it
+    async cancelFileConversion(filePath: string): Promise<boolean> {
+        return await invoke<boolean>('cancel_file_conversion', {filePath});
+    },
+
+    // Unix only; rejects elsewhere
+    async pauseConversion(): Promise<void> {
+        await invoke('pause_conversion');
+    },
+
+    async resumeConversion(): Promise<void> {
+        await invoke('resume_conversion');
+    },
+
+    async getInterruptedJob(): Promise<BatchJob | null> {
+        return await invoke<BatchJob | null>('get_interrupted_job');
+    },
+
+    async resumeInterruptedJob(): Promise<string> {
+        return await invoke<string>('resume_interrupted_job');
+    },
+
+    async discardInterruptedJob(): Promise<void> {
+        await invoke('discard_interrupted_job');
+    },
+
+    async startWatch(params: VideoConversionParams): Promise<void> {
+        await invoke('start_watch', {params});
+    },
+
+    async stopWatch(): Promise<void> {
+        await invoke('stop_watch');
+    },
+
+    async getWatchStatus(): Promise<WatchStatus> {
+        return await invoke<WatchStatus>('get_watch_status');
+    },
+
     async openLicense(license: LicenseType): Promise<void> {
         await invoke('open_bundled_license', {license});
     },
@@ -31,5 +114,23 @@ export const tauriAPI = {
         return listen<ConversionProgress>('conversion-progress', (event) => {
             callback(event.payload);
         });
+    },
+
+    onScanProgress(callback: (event: ScanFileEvent) => void) {
+        return listen<ScanFileEvent>('scan-progress', (event) => {
+            callback(event.payload);
+        });
+    },
+
+    onScanFinished(callback: (summary: ScanSummary) => void) {
+        return listen<ScanSummary>('scan-finished', (event) => {
+            callback(event.payload);
+        });
+    },
+
+    onWatchFile(callback: (event: WatchFileEvent) => void) {
+        return listen<WatchFileEvent>('watch-file', (event) => {
+            callback(event.payload);
+        });
     }
 };
@@ -47,7 +47,7 @@ export function useVideoConversion() {
         store.folderScanning = true;
 
         try {
-            const files = await tauriAPI.getVideoFiles(folderPath);
+            const { files } = await tauriAPI.getVideoFiles(folderPath, undefined, store.outputFolder);
             if (scanId === currentScanId.value) {
                 store.videoFiles = files.map((f, index) => ({ ...f, convert: true, progress: 0, position: index }));
             }
@@ -85,14 +85,14 @@ export function useVideoConversion() {
                 use_custom_video_quality: store.useCustomVideoQuality,
                 video_quality: store.videoQuality,
                 files: selectedFiles.value
-                    .filter(f => f.status !== ConversionStatus.Success)
+                    .filter(f => f.status !== ConversionStatus.Success && f.status !== ConversionStatus.Skipped)
                     .map(f => f.path),
                 use_gpu: store.useGpu,
                 gpu_type: store.gpuInfo?.gpu_type !== 'None' ? store.gpuInfo?.gpu_type : undefined,
             });
 
             const hasErrors = selectedFiles.value.some(f =>
-                f.convert && f.status !== ConversionStatus.Success
+                f.convert && f.status !== ConversionStatus.Success && f.status !== ConversionStatus.Skipped
             );
 
             showMsg(
@@ -147,7 +147,7 @@ export function useVideoConversion() {
         unlistenProgress.value = await tauriAPI.onConversionProgress((data) => {
             if (!data || data.status === ConversionStatus.Cancelled) return;
 
-            const video = store.videoFiles.find(v => v.name === data.current_file);
+            const video = store.videoFiles.find(v => v.path === data.file_path);
             if (video) {
                 video.progress = Math.floor(data.percentage) || 0;
                 video.status = data.status;
@@ -38,6 +38,8 @@ export default {
         26: "Выяўлена спроба выхаду за межы дазволенай дырэкторыі (path traversal). З меркаванняў бяспекі шлях да файла знаходзіцца па-за дазволенай тэчкай.",
         27: "Няправільны шлях да файла. Пераканайцеся, што шлях існуе і даступны.",
         28: "Адсутнічае файл ліцэнзіі.",
+        29: "Недапушчальны тэг метаданых вываду.",
+        30: "Недапушчальны фільтр сканавання. Праверце шаблоны і дыяпазоны.",
     },
     mainView: {
         setup: {
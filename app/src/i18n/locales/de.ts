@@ -38,6 +38,8 @@ export default {
         26: "Versuch, das erlaubte Verzeichnis zu verlassen (path traversal) erkannt. Der Dateipfad liegt aus Sicherheitsgründen außerhalb des erlaubten Verzeichnisses.",
         27: "Ungültiger Dateipfad. Bitte stellen Sie sicher, dass der Pfad existiert und zugänglich ist.",
         28: "Die Lizenzdatei fehlt.",
+        29: "Ungültiges Metadaten-Tag für die Ausgabe.",
+        30: "Ungültiger Scan-Filter. Bitte Muster und Bereiche prüfen.",
     },
     mainView: {
         setup: {
@@ -38,6 +38,8 @@ export default {
         26: "Path traversal detected. The file path is outside the allowed directory for security reasons.",
         27: "Invalid file path provided. Please ensure the path exists and is accessible.",
         28: "The license file is missing.",
+        29: "Invalid output metadata tag.",
+        30: "Invalid scan filter. Check the patterns and ranges.",
     },
     mainView: {
         setup: {
@@ -38,6 +38,8 @@ export default {
         26: "Se detectó un intento de escape de directorios (path traversal). Por seguridad, la ruta del archivo queda fuera del directorio permitido.",
         27: "Ruta de archivo inválida. Asegúrate de que la ruta existe y es accesible.",
         28: "Falta el archivo de licencia.",
+        29: "Etiqueta de metadatos de salida no válida.",
+        30: "Filtro de escaneo no válido. Revisa los patrones y los rangos.",
     },
     mainView: {
         setup: {
@@ -38,6 +38,8 @@ export default {
         26: "Tentative d'évasion de répertoire (path traversal) détectée. Pour des raisons de sécurité, le chemin du fichier est en dehors du répertoire autorisé.",
         27: "Chemin de fichier invalide. Assurez-vous que le chemin existe et est accessible.",
         28: "Le fichier de licence est manquant.",
+        29: "Balise de métadonnées de sortie invalide.",
+        30: "Filtre d'analyse invalide. Vérifiez les motifs et les plages.",
     },
     mainView: {
         setup: {
@@ -38,6 +38,8 @@ export default {
         26: "Rilevato tentativo di uscita dalla directory consentita (path traversal). Per sicurezza, il percorso del file è fuori dalla cartella autorizzata.",
         27: "Percorso file non valido. Assicurati che il percorso esista e sia accessibile.",
         28: "Manca il file di licenza.",
+        29: "Tag di metadati di output non valido.",
+        30: "Filtro di scansione non valido. Controlla i modelli e gli intervalli.",
     },
     mainView: {
         setup: {
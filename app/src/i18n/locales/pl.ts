@@ -38,6 +38,8 @@ export default {
         26: "Wykryto próbę wyjścia poza dozwolony katalog (path traversal). Ze względów bezpieczeństwa ścieżka pliku jest poza katalogiem dozwolonym.",
         27: "Nieprawidłowa ścieżka pliku. Upewnij się, że ścieżka istnieje i jest dostępna.",
         28: "Brak pliku licencji.",
+        29: "Nieprawidłowy znacznik metadanych wyjściowych.",
+        30: "Nieprawidłowy filtr skanowania. Sprawdź wzorce i zakresy.",
     },
     mainView: {
         setup: {
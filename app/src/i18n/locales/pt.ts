@@ -38,6 +38,8 @@ export default {
         26: "Tentativa de saída do diretório permitido (path traversal) detectada. Por segurança, o caminho do arquivo está fora da pasta autorizada.",
         27: "Caminho de arquivo inválido. Certifique-se de que o caminho existe e está acessível.",
         28: "Arquivo de licença ausente.",
+        29: "Etiqueta de metadados de saída inválida.",
+        30: "Filtro de varredura inválido. Verifique os padrões e intervalos.",
     },
     mainView: {
         setup: {
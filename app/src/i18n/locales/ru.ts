@@ -38,6 +38,8 @@ export default {
         26: "Обнаружена попытка выхода за пределы разрешённого каталога (path traversal). По соображениям безопасности путь к файлу находится вне разрешённой директории.",
         27: "Неверный путь к файлу. Убедитесь, что путь существует и доступен.",
         28: "Отсутствует файл лицензии.",
+        29: "Недопустимый тег метаданных вывода.",
+        30: "Недопустимый фильтр сканирования. Проверьте шаблоны и диапазоны.",
     },
     mainView: {
         setup: {
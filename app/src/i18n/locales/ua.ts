@@ -38,6 +38,8 @@ export default {
         26: "Виявлено спробу виходу за межі дозволеної директорії (path traversal). З міркувань безпеки шлях до файлу поза дозволеною текою.",
         27: "Невірний шлях до файлу. Переконайтеся, що шлях існує та доступний.",
         28: "Відсутній файл ліцензії.",
+        29: "Недійсний тег метаданих виводу.",
+        30: "Недійсний фільтр сканування. Перевірте шаблони та діапазони.",
     },
     mainView: {
         setup: {
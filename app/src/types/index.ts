@@ -1,6 +1,7 @@
 export interface VideoFile {
     path: string;
     name: string;
+    relative_dir?: string; // subfolder below the scanned folder, '' for the top level
     size: number;
     // duration?: number;
     convert: boolean;
@@ -8,6 +9,67 @@ export interface VideoFile {
     position?: number;
     status?: ConversionStatus;
     thumbnail?: string; //base64 data url
+    provenance?: ConversionProvenance; // set when the file is already an output of Free FPS
+    clip?: CameraClip; // set for clips found in a camera card layout
+    duplicate_of?: string; // path of an identical file listed earlier
+    container?: string; // sniffed from the header when the extension is unknown or missing
+}
+
+export enum CardLayout {
+    Avchd = "Avchd",
+    Xavc = "Xavc",
+    Dcim = "Dcim",
+}
+
+export interface CameraClip {
+    layout: CardLayout;
+    display_name: string; // "<recording time>_<original name>", also used for output names
+    recorded_at?: string; // RFC 3339
+    device?: string;
+    creation_time?: string; // from the camera sidecar, UTC
+}
+
+// Provenance tag written into every converted file
+export interface ConversionProvenance {
+    source_fps: number;
+    target_fps: number;
+    mode: string; // "retime"
+    speed_factor: number; // target fps / source fps
+    encoder: string;
+    app_version: string;
+    source_hash: string; // hash prefix of the source file
+    converted_at?: number; // unix seconds when the conversion started
+}
+
+export interface TimecodeInfo {
+    start: string; // e.g. "01:00:00:00", or "01:00:00;00" for drop-frame
+    rate: number; // timecode rate in fps
+    drop_frame: boolean;
+}
+
+export interface StreamInfo {
+    index: number;
+    codec_type: string; // "video", "audio", "subtitle", "data" or "attachment"
+    codec_name?: string;
+    codec_tag?: string; // e.g. "avc1", "tmcd"
+    width?: number;
+    height?: number;
+    fps?: number;
+    attached_pic: boolean; // cover art
+    timecode?: string;
+}
+
+export interface VideoProbe {
+    fps: number;
+    duration_sec: number; // 0 when the container reports no duration
+    creation_time?: string;
+    timecode?: TimecodeInfo; // start timecode (QuickTime tmcd track or timecode tag)
+    video_codec?: string;
+    width?: number;
+    height?: number;
+    has_audio: boolean;
+    streams: StreamInfo[];
+    provenance?: ConversionProvenance;
 }
 
 // GPU types for hardware-accelerated encoding
@@ -22,9 +84,11 @@ export interface GpuInfo {
 
 export interface VideoConversionParams {
     input_folder: string; //input folder path
+    input_roots?: string[]; // allowed source folders (e.g. every folder picked in the dialog), input_folder alone if empty
     output_folder: string; //output folder path (if empty, use input folder to create output folder inside with the name "converted_videos_${target_fps}fps")
     target_fps: number; //target fps
     cpu_limit: number; //cpu limit percentage (0-100)
+    workers?: number; // files converted at the same time (1-16), cpu_limit threads are split between them
     keep_audio: boolean; // if true keep audio in video
     audio_bitrate: number; // output audio bitrate in video (if keep_audio = true)
     use_custom_video_quality: boolean; // if true use custom video quality - video_quality (crf, 0-51, lower is better quality). If false:
@@ -32,6 +96,159 @@ export interface VideoConversionParams {
     files: string[]; //array of file paths to convert
     use_gpu: boolean; // if true use GPU acceleration
     gpu_type?: string; // GPU type: 'Nvidia', 'Amd', 'Intel' or undefined for auto-detect
+    incremental?: boolean; // skip files whose output already exists and is up to date
+    scan?: ScanOptions; // how input_folder is scanned when files is empty
+    metadata?: MetadataTag[]; // extra output tags, values may use {source_name}, {source_stem}, {source_fps}, {target_fps}, {date}
+    skip_duplicates?: boolean; // leave out files identical to one earlier in the batch
+    join_chapters?: string[][]; // chapter sequences from ScanResult.chapters, each converted into one output
+    overrides?: Record<string, FileOverrides>; // per-file settings by source path
+    on_conflict?: ConflictPolicy; // what to do when an output already exists, Overwrite if not set
+}
+
+export enum ConflictPolicy {
+    Overwrite = "Overwrite",
+    Skip = "Skip", // keep the existing output and don't convert the file
+    Rename = "Rename", // write next to it with a _1, _2, ... suffix
+}
+
+export enum OutputAction {
+    Created = "Created",
+    Overwritten = "Overwritten",
+    Kept = "Kept", // existing output kept, the file was not converted
+    Renamed = "Renamed", // written under a suffixed name
+}
+
+// Unset fields keep the batch value
+export interface FileOverrides {
+    target_fps?: number;
+    keep_audio?: boolean;
+    audio_bitrate?: number;
+    use_custom_video_quality?: boolean;
+    video_quality?: number;
+    use_gpu?: boolean;
+    gpu_type?: string;
+    metadata?: MetadataTag[];
+}
+
+// Batch settings with the file's overrides applied
+export interface FileSettings {
+    target_fps: number;
+    keep_audio: boolean;
+    audio_bitrate: number;
+    use_custom_video_quality: boolean;
+    video_quality: number;
+    use_gpu: boolean;
+    gpu_type?: string;
+    metadata: MetadataTag[];
+}
+
+export interface ScanOptions {
+    recursive?: boolean; // descend into subfolders
+    max_depth?: number; // deepest subfolder level to scan, unlimited if not set
+    follow_symlinks?: boolean; // symlink targets must still be inside the scanned folder
+    extensions?: string[]; // extensions treated as videos, without the dot
+    sniff?: boolean; // detect videos by content and drop files without a video stream
+    filter?: ScanFilter;
+    detect_chapters?: boolean; // report chaptered recordings in ScanResult.chapters
+    detect_duplicates?: boolean; // hash same-size files and set VideoFile.duplicate_of
+    sort_by?: SortKey; // also the order batches are converted in
+    descending?: boolean;
+}
+
+export enum SortKey {
+    Name = "Name", // natural order of the subfolder, then the file name
+    Size = "Size",
+    Modified = "Modified",
+    CreationTime = "CreationTime", // camera sidecar or container creation_time
+    Duration = "Duration",
+    Fps = "Fps",
+}
+
+// Unset bounds and empty lists match everything
+export interface ScanFilter {
+    include?: string[]; // globs matched against the file name and the path relative to the scanned folder
+    exclude?: string[]; // e.g. '*_proxy*'
+    min_size?: number; // bytes
+    max_size?: number;
+    min_duration?: number; // seconds
+    max_duration?: number;
+    min_fps?: number;
+    max_fps?: number;
+    min_width?: number;
+    max_width?: number;
+    min_height?: number;
+    max_height?: number;
+    codecs?: string[]; // allowed video codecs, e.g. 'h264', 'hevc', 'prores'
+}
+
+export enum SkipReason {
+    UnsupportedExtension = "UnsupportedExtension",
+    NoVideoStream = "NoVideoStream",
+    ProbeFailed = "ProbeFailed",
+    Filtered = "Filtered",
+    Symlink = "Symlink",
+    OutsideFolder = "OutsideFolder",
+    Unreadable = "Unreadable",
+}
+
+export interface SkippedFile {
+    path: string;
+    name: string;
+    reason: SkipReason;
+    details?: string;
+}
+
+export interface ScanResult {
+    files: VideoFile[];
+    skipped: SkippedFile[];
+    chapters?: ChapterGroup[];
+}
+
+export interface HistogramBucket {
+    label: string; // '29.97', 'h264', '3840x2160'
+    count: number;
+    duration: number; // seconds of footage in this bucket
+}
+
+export interface FolderSummary {
+    file_count: number;
+    total_size: number;
+    total_duration: number; // seconds, over the probed files
+    unprobed: number; // files left out of durations and histograms
+    fps: HistogramBucket[]; // largest first
+    codecs: HistogramBucket[];
+    resolutions: HistogramBucket[];
+    estimated_output_duration?: number; // seconds at the target fps
+}
+
+// Files of one chaptered recording, in playback order
+export interface ChapterGroup {
+    files: string[];
+    name: string; // first chapter, the joined output is named after it
+    duration?: number; // combined, in seconds
+}
+
+export interface ScanFileEvent {
+    scan_id: number;
+    index: number; // 0-based, in discovery order
+    file: VideoFile;
+    probe?: VideoProbe;
+}
+
+export interface ScanSummary {
+    scan_id: number;
+    found: number;
+    skipped: SkippedFile[];
+    chapters: ChapterGroup[];
+    duplicates: Record<string, string>; // duplicate path -> VideoFile.duplicate_of
+    cancelled: boolean;
+    error?: AppError;
+}
+
+export interface MetadataTag {
+    key: string;
+    value: string;
+    stream?: string; // stream specifier ('v', 'a', 'a:1'...), global tag if not set
 }
 
 export enum LicenseType {
@@ -60,21 +277,77 @@ export enum ErrorCode {
     PathTraversalDetected = 26,
     InvalidInputPath = 27,
     LicenseNotFound = 28,
+    InvalidMetadataTag = 29,
+    InvalidScanFilter = 30,
 }
 
 export type AppError = { code: ErrorCode; details?: string };
 
+export enum WatchFileStatus {
+    Queued = "Queued",
+    Started = "Started",
+    Finished = "Finished",
+    Failed = "Failed",
+}
+
+// Payload of the 'watch-file' event
+export interface WatchFileEvent {
+    path: string;
+    name: string;
+    status: WatchFileStatus;
+    error?: AppError;
+}
+
+export interface WatchStatus {
+    active: boolean;
+    folders: string[]; // watched input folders
+    queued: number; // files waiting for their turn
+    current?: string; // file being converted
+    finished: number;
+    failed: number;
+}
+
 export enum ConversionStatus {
     Processing = "Processing",
     Success = "Success",
     Error = "Error",
     Cancelled = "Cancelled",
+    Skipped = "Skipped", // incremental run: output already up to date
+    Paused = "Paused",
+}
+
+export enum JobFileState {
+    Pending = "Pending",
+    Running = "Running", // being converted; after a crash its output is partial
+    Done = "Done",
+    Skipped = "Skipped",
+    Failed = "Failed",
+}
+
+export interface JobEntry {
+    source: string;
+    chapters: string[]; // joined after source
+    output: string;
+    state: JobFileState;
+}
+
+// Journal of a batch interrupted by a crash or by closing the app
+export interface BatchJob {
+    started_at: string; // RFC 3339
+    params: VideoConversionParams;
+    files: JobEntry[];
 }
 
 export interface ConversionProgress {
     current_file: string;
+    file_path: string; // identifies the file when several are converted at once
     current_file_index: number;
     total_files: number;
     percentage: number;
     status: ConversionStatus; //pub enum ConversionStatus {Conversion,Success,Error,None,}
+    worker: number; // worker converting the file, 0..workers
+    settings?: FileSettings; // effective settings of the file, sent with its status changes
+    encoder?: string; // encoder that produced the output, sent with Success; libx264 after a GPU fallback
+    output_action?: OutputAction; // sent when the file starts, ends or is skipped
+    output_path?: string;
 }
\ No newline at end of file